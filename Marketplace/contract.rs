@@ -5,22 +5,51 @@ use std::marker::PhantomData;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{from_json, StdError}; 
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Coin, Addr, Empty};
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Coin, Addr, Empty, CosmosMsg, BankMsg, Uint128, Storage, Timestamp, BlockInfo, Event};
 use cw2::set_contract_version;
-use cw721::{Cw721ReceiveMsg, OwnerOfResponse};
+use cw721::{ApprovalResponse, Cw721QueryMsg, Cw721ReceiveMsg, Expiration, OperatorResponse, OwnerOfResponse};
 use cw721_base::helpers::Cw721Contract;
 use cw_storage_plus::Bound;
-use crate::msg::ListingsResponse;
+use crate::msg::{ListingsResponse, ListingsPageResponse, ListingFilter, ListingSort, ListingCursor, BidderBid, BidderBidsResponse};
 use crate::msg::BidResponse;
 
 
 use crate::error::ContractError;
-use crate::msg::{ListingResponse, ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg};
-use crate::state::{State, STATE, LISTINGS, Collection, Listing, Bid, APPROVED_COLLECTIONS};
+use crate::upgrades;
+use crate::msg::{BatchBuyItem, ListingResponse, ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg};
+use crate::msg::{CollectionQueryMsg, Cw2981QueryMsg, CheckRoyaltiesResponse, RoyaltiesInfoResponse, FeesResponse, DutchPriceResponse};
+use crate::msg::{SalesResponse, CollectionStatsResponse, CollectionsResponse, RolesResponse, RoleMembersResponse, LegacyBid, LegacyBidsResponse};
+use crate::msg::{ReceiveNftMsg, NftInfoResponse, TokenTraitsResponse, CollectionOffersResponse, TokenOffersResponse, BundlesResponse, TradesResponse};
+use crate::state::{Role, ROLES, PauseScope, PAUSE_STATE, PAYMENT_DENOM, DEFAULT_DENOM, listings, save_listing, remove_listing, BIDS_BY_BIDDER, LEGACY_LISTINGS, LEGACY_BIDS, Collection, Listing, Bid, APPROVED_COLLECTIONS, COLLECTIONS_BY_ADDR, save_collection, remove_collection};
+use crate::state::{Auction, ListingCustody, ListingKind, is_expired};
+use crate::state::{CollectionOffer, TraitFilter, COLLECTION_OFFERS, COLLECTION_OFFER_COUNT, COLLECTION_OFFERS_BY_COLLECTION, save_collection_offer, remove_collection_offer};
+use crate::state::{FeeConfig, FEE_CONFIG, FEE_TOTALS};
+use crate::state::{TokenOffer, token_offers};
+use crate::state::{Bundle, BundleItem, TokenRef, BUNDLES, BUNDLE_COUNT, BUNDLES_BY_COLLECTION, save_bundle, remove_bundle};
+use crate::state::{Trade, TRADES, TRADE_COUNT, TRADES_BY_COLLECTION, save_trade, remove_trade};
+use crate::state::{Sale, SaleKind, sales, record_sale, COLLECTION_STATS};
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:marketplace";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const CONTRACT_NAME: &str = "crates.io:marketplace";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Fees are expressed in basis points of the sale price
+const MAX_FEE_BPS: u64 = 10_000;
+
+// Bids placed within this many seconds of an auction's end push the end back by the same amount
+const AUCTION_EXTENSION_SECONDS: u64 = 600;
+
+// Upper bound on the NFTs transferred by a single bundle sale
+const MAX_BUNDLE_ITEMS: usize = 25;
+
+// Upper bound on the NFTs on each side of a trade
+const MAX_TRADE_ITEMS: usize = 25;
+
+// Upper bound on the listings bought by a single BatchBuy
+const MAX_BATCH_ITEMS: usize = 25;
+
+// Window sizes for collection volume stats
+const DAY_SECONDS: u64 = 86_400;
 
 // Pagination
 const DEFAULT_LIMIT: u32 = 30;
@@ -33,12 +62,23 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let state = State {
-        owner: info.sender.clone(),
-        approved_collections: vec![],
-    };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    STATE.save(deps.storage, &state)?;
+    cw_ownable::initialize_owner(deps.storage, deps.api, Some(info.sender.as_str()))?;
+    if let Some(denom) = &msg.denom {
+        if denom.is_empty() {
+            return Err(ContractError::CustomError { val: "Payment denom is empty".to_string() });
+        }
+        PAYMENT_DENOM.save(deps.storage, denom)?;
+    }
+
+    for collection in msg.approved_collections {
+        validate_collection(deps.as_ref(), &collection)?;
+        if APPROVED_COLLECTIONS.has(deps.storage, &collection.name) {
+            return Err(ContractError::CustomError { val: "Collection name already exists".to_string() });
+        }
+        ensure_address_unapproved(deps.storage, &collection.contract_addr)?;
+        save_collection(deps.storage, &collection)?;
+    }
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -46,6 +86,38 @@ pub fn instantiate(
         .add_attribute("count", msg.count.to_string()))
 }
 
+/// The pause scope an execute message falls under. Exit paths (delisting, bid and offer
+/// withdrawal, cancellations, expiry cleanup, settling ended auctions) have none so they keep
+/// working during an incident.
+fn pause_scope(msg: &ExecuteMsg) -> Option<PauseScope> {
+    match msg {
+        ExecuteMsg::ListWithApproval { .. }
+        | ExecuteMsg::EditListing { .. }
+        | ExecuteMsg::CreateBundle { .. }
+        | ExecuteMsg::ProposeTrade { .. } => Some(PauseScope::Listings),
+        ExecuteMsg::PlaceBid { .. }
+        | ExecuteMsg::MakeOffer { .. }
+        | ExecuteMsg::CreateCollectionOffer { .. }
+        | ExecuteMsg::PlaceBundleBid { .. } => Some(PauseScope::Bids),
+        ExecuteMsg::BuyNow { .. }
+        | ExecuteMsg::BatchBuy { .. }
+        | ExecuteMsg::AcceptBid { .. }
+        | ExecuteMsg::BuyBundle { .. }
+        | ExecuteMsg::AcceptBundleBid { .. }
+        | ExecuteMsg::AcceptTrade { .. } => Some(PauseScope::Purchases),
+        _ => None,
+    }
+}
+
+fn assert_not_paused(storage: &dyn Storage, scope: PauseScope) -> Result<(), ContractError> {
+    let pause = PAUSE_STATE.may_load(storage)?.unwrap_or_default();
+    if pause.is_paused(scope) {
+        return Err(ContractError::Paused { scope: scope.as_str().to_string() });
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -53,36 +125,118 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    if let Some(scope) = pause_scope(&msg) {
+        assert_not_paused(deps.storage, scope)?;
+    }
+
     match msg { 
+        ExecuteMsg::UpdateOwnership(action) => try_update_ownership(deps, env, info, action),
+        ExecuteMsg::Pause { scopes } => try_update_pause(deps, info, scopes, true),
+        ExecuteMsg::Unpause { scopes } => try_update_pause(deps, info, scopes, false),
+        ExecuteMsg::GrantRole { role, address } => try_grant_role(deps, info, role, address),
+        ExecuteMsg::RevokeRole { role, address } => try_revoke_role(deps, info, role, address),
         ExecuteMsg::AddApprovedCollection { collection } => try_add_approved_collection(deps, info, collection),
+        ExecuteMsg::RemoveApprovedCollection { name } => try_remove_approved_collection(deps, info, name),
+        ExecuteMsg::ClearCollection { name, limit } => try_clear_collection(deps, info, name, limit),
+        ExecuteMsg::RenameCollection { name, new_name } => try_rename_collection(deps, info, name, new_name),
+        ExecuteMsg::UpdateCollectionAddress { name, contract_addr } => try_update_collection_address(deps, info, name, contract_addr),
+        ExecuteMsg::SetCollectionPaused { name, paused } => try_set_collection_paused(deps, info, name, paused),
         ExecuteMsg::DelistToken { collection_name, token_id } => try_delist_token(deps, info, collection_name, token_id),
-        ExecuteMsg::PlaceBid { collection, token_id, amount } => try_place_bid(deps, env, info, collection, token_id, amount),
+        ExecuteMsg::PlaceBid { collection, token_id, amount, expires } => try_place_bid(deps, env, info, collection, token_id, amount, expires),
         ExecuteMsg::RemoveBid { collection, token_id } => try_remove_bid(deps, info, collection, token_id),
         ExecuteMsg::ReceiveNft(msg) => try_receive_nft(env, deps, info, msg),
         ExecuteMsg::BuyNow { collection, token_id } => try_buy_now(deps, env, info, collection, token_id),
         ExecuteMsg::AcceptBid { collection, token_id, bidder } => try_accept_bid(deps, env, info, collection, token_id, bidder),
         ExecuteMsg::EditListing { collection, token_id, new_amount } => try_edit_listing(deps, info, collection, token_id, new_amount),
         ExecuteMsg::RemoveListing { collection, token_id } => try_remove_listing(deps, info, collection, token_id),
+        ExecuteMsg::SetRoyaltyCap { collection, max_royalty_percentage } => try_set_royalty_cap(deps, info, collection, max_royalty_percentage),
+        ExecuteMsg::SetFee { fee_bps, fee_collector } => try_set_fee(deps, info, fee_bps, fee_collector),
+        ExecuteMsg::SetCollectionFee { collection, fee_bps } => try_set_collection_fee(deps, info, collection, fee_bps),
+        ExecuteMsg::WithdrawFees { amount, recipient } => try_withdraw_fees(deps, info, amount, recipient),
+        ExecuteMsg::ResolveLegacyBid { collection, token_id, bidder, refund } => try_resolve_legacy_bid(deps, info, collection, token_id, bidder, refund),
+        ExecuteMsg::SettleAuction { collection, token_id } => try_settle_auction(deps, env, collection, token_id),
+        ExecuteMsg::RemoveExpiredBids { collection, token_id } => try_remove_expired_bids(deps, env, collection, token_id),
+        ExecuteMsg::ReturnExpiredListing { collection, token_id } => try_return_expired_listing(deps, env, collection, token_id),
+        ExecuteMsg::CreateCollectionOffer { collection, filter, price, quantity, expires } => try_create_collection_offer(deps, env, info, collection, filter, price, quantity, expires),
+        ExecuteMsg::CancelCollectionOffer { offer_id } => try_cancel_collection_offer(deps, info, offer_id),
+        ExecuteMsg::MakeOffer { collection, token_id, amount, expires } => try_make_offer(deps, env, info, collection, token_id, amount, expires),
+        ExecuteMsg::CancelOffer { collection, token_id } => try_cancel_offer(deps, info, collection, token_id),
+        ExecuteMsg::ListWithApproval { collection, token_id, price, kind, expires, reserved_for } => {
+            try_list_with_approval(deps, env, info, collection, token_id, price, kind, expires, reserved_for)
+        },
+        ExecuteMsg::InvalidateListing { collection, token_id } => try_invalidate_listing(deps, env, collection, token_id),
+        ExecuteMsg::CreateBundle { items, price, expires } => try_create_bundle(deps, env, info, items, price, expires),
+        ExecuteMsg::CancelBundle { bundle_id } => try_cancel_bundle(deps, info, bundle_id),
+        ExecuteMsg::BuyBundle { bundle_id } => try_buy_bundle(deps, env, info, bundle_id),
+        ExecuteMsg::PlaceBundleBid { bundle_id, amount, expires } => try_place_bundle_bid(deps, env, info, bundle_id, amount, expires),
+        ExecuteMsg::RemoveBundleBid { bundle_id } => try_remove_bundle_bid(deps, info, bundle_id),
+        ExecuteMsg::AcceptBundleBid { bundle_id, bidder } => try_accept_bundle_bid(deps, env, info, bundle_id, bidder),
+        ExecuteMsg::ProposeTrade { counterparty, offered, requested, requested_funds, expires } => {
+            try_propose_trade(deps, env, info, counterparty, offered, requested, requested_funds, expires)
+        },
+        ExecuteMsg::AcceptTrade { trade_id } => try_accept_trade(deps, env, info, trade_id),
+        ExecuteMsg::CancelTrade { trade_id } => try_cancel_trade(deps, info, trade_id),
+        ExecuteMsg::BatchBuy { items, skip_unavailable } => try_batch_buy(deps, env, info, items, skip_unavailable),
     }
 }
 
 pub fn try_list_token(
     deps: DepsMut,
-    listing: Listing,
+    env: &Env,
+    collection: &Collection,
+    mut listing: Listing,
 ) -> Result<Response, ContractError> {
     // Check and handle coin denominations using price (Vec<Coin>)
     // if listing.price.as_ref().map_or(true, Vec::is_empty) {
     //     return Err(ContractError::InvalidPrice {});
     // }
 
+    // Listings never start with bids, every bid is escrowed through PlaceBid
+    listing.bids = None;
+
+    if listing.is_expired(&env.block) {
+        return Err(ContractError::ListingExpired {});
+    }
+
+    // Private listings are fixed-price sales to one buyer
+    if listing.reserved_for.is_some() && listing.kind != ListingKind::FixedPrice {
+        return Err(ContractError::CustomError { val: "Only fixed-price listings can be reserved".to_string() });
+    }
+
+    if let ListingKind::Auction(auction) = &mut listing.kind {
+        // Auctions run until end_time and are closed by SettleAuction, not by expiry
+        if auction.end_time <= auction.start_time || auction.end_time <= env.block.time || listing.expires.is_some() {
+            return Err(ContractError::InvalidAuction {});
+        }
+        auction.highest_bid = None;
+        listing.price = None;
+    }
+
+    if let ListingKind::Dutch(dutch) = &listing.kind {
+        if dutch.end_time <= dutch.start_time || dutch.start_price < dutch.floor_price {
+            return Err(ContractError::InvalidAuction {});
+        }
+        listing.price = None;
+    }
+
+    // A live listing is never overwritten, a stale approval listing is closed and its bidders refunded
+    let mut response = Response::new();
+    let key = (listing.collection.as_str(), listing.token_id.as_str());
+    if let Some(previous) = listings().may_load(deps.storage, key)? {
+        if is_listing_valid(deps.as_ref(), env, &previous, collection) {
+            return Err(ContractError::AlreadyListed {});
+        }
+        response = invalidate_listing(deps.storage, &previous, vec![])?;
+    }
+
     // Save the listing data
-    let key = format!("{}:{}", listing.collection, listing.token_id);
-    LISTINGS.save(deps.storage, &key, &listing)?;
+    listing.listed_at = Some(env.block.time);
+    save_listing(deps.storage, &listing)?;
 
-    Ok(Response::new().add_attribute("method", "try_list_token"))
+    Ok(response)
 }
 
-fn _only_owner(
+fn only_owner(
     deps: Deps,
     info: &MessageInfo,
     collection: &Addr,
@@ -98,36 +252,135 @@ fn _only_owner(
 }
 
 fn try_receive_nft(
-    _env: Env,
+    env: Env,
     deps: DepsMut,
     info: MessageInfo,
     nft_msg: Cw721ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    // Decode the listing details from the nft_msg
-    let listing: Listing = from_json(&nft_msg.msg)
+    // The listing owner and collection come from the cw721 send itself, never from the payload
+    let receive_msg: ReceiveNftMsg = from_json(&nft_msg.msg)
         .map_err(|_| ContractError::DeserializeError {})?;
-    
-    // Verify the collection is authorized
-    verify_authorized_collection(deps.as_ref(), &listing.collection)?;
 
-    // Retrieve the contract address for the given collection
-    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &listing.collection)
-        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    let scope = match receive_msg {
+        ReceiveNftMsg::List { .. } => PauseScope::Listings,
+        ReceiveNftMsg::FillCollectionOffer { .. } | ReceiveNftMsg::AcceptOffer { .. } => PauseScope::Purchases,
+    };
+    assert_not_paused(deps.storage, scope)?;
+
+    match receive_msg {
+        ReceiveNftMsg::List { price, kind, expires, reserved_for } => {
+            try_list_received_nft(deps, env, info, nft_msg, price, kind, expires, reserved_for)
+        },
+        ReceiveNftMsg::FillCollectionOffer { offer_id } => try_fill_collection_offer(deps, env, info, nft_msg, offer_id),
+        ReceiveNftMsg::AcceptOffer { bidder } => try_accept_offer(deps, env, info, nft_msg, bidder),
+    }
+}
 
-    // Verify the collection is the token's contract address
-    if collection_data.contract_addr != info.sender {
-        return Err(ContractError::Unauthorized {});
+#[allow(clippy::too_many_arguments)]
+fn try_list_received_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_msg: Cw721ReceiveMsg,
+    price: Option<u128>,
+    kind: ListingKind,
+    expires: Option<Expiration>,
+    reserved_for: Option<String>,
+) -> Result<Response, ContractError> {
+    // Only approved collections can list, and the sending contract is the collection
+    let collection_data = collection_by_addr(deps.storage, &info.sender)?;
+    let owner = deps.api.addr_validate(&nft_msg.sender)?;
+    let reserved_for = reserved_for.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+
+    let listing = Listing {
+        token_uri: query_token_uri(deps.as_ref(), &collection_data, &nft_msg.token_id)?,
+        collection: collection_data.name.clone(),
+        token_id: nft_msg.token_id.clone(),
+        owner: owner.clone(),
+        price,
+        bids: None,
+        kind,
+        expires,
+        reserved_for,
+        custody: ListingCustody::Escrow,
+        listed_at: None,
+    };
+
+    // Create the listing using try_list_token function
+    let response = try_list_token(deps, &env, &collection_data, listing)?;
+
+    Ok(response
+        .add_attribute("method", "try_receive_nft")
+        .add_attribute("owner", owner)
+        .add_attribute("token_id", nft_msg.token_id))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_list_with_approval(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    token_id: String,
+    price: Option<u128>,
+    kind: ListingKind,
+    expires: Option<Expiration>,
+    reserved_for: Option<String>,
+) -> Result<Response, ContractError> {
+    verify_authorized_collection(deps.as_ref(), &collection)?;
+    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)?;
+
+    // Auction bids are escrowed against the NFT, so auctions still need custody
+    if matches!(kind, ListingKind::Auction(_)) {
+        return Err(ContractError::CustomError { val: "Auctions require escrow".to_string() });
     }
 
-    // Ensure the received listing data is valid
-    if listing.token_id != nft_msg.token_id {
-        return Err(ContractError::InvalidTokenId {});
+    only_owner(deps.as_ref(), &info, &collection_data.contract_addr, token_id.clone())?;
+    if !is_approved(deps.as_ref(), &env, &collection_data, &info.sender, &token_id) {
+        return Err(ContractError::NotApproved {});
     }
 
-    // Create the listing using try_list_token function
-    try_list_token(deps, listing)?;
+    let reserved_for = reserved_for.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let listing = Listing {
+        token_uri: query_token_uri(deps.as_ref(), &collection_data, &token_id)?,
+        collection,
+        token_id: token_id.clone(),
+        owner: info.sender.clone(),
+        price,
+        bids: None,
+        kind,
+        expires,
+        reserved_for,
+        custody: ListingCustody::Approval,
+        listed_at: None,
+    };
+    let response = try_list_token(deps, &env, &collection_data, listing)?;
 
-    Ok(Response::new().add_attribute("method", "try_receive_nft"))
+    Ok(response
+        .add_attribute("method", "try_list_with_approval")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id))
+}
+
+/// Removes an approval listing whose seller no longer owns the token or has revoked the approval
+pub fn try_invalidate_listing(
+    deps: DepsMut,
+    env: Env,
+    collection: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let key = (collection.as_str(), token_id.as_str());
+    let listing = listings().load(deps.storage, key)
+        .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
+    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+
+    if is_listing_valid(deps.as_ref(), &env, &listing, &collection_data) {
+        return Err(ContractError::CustomError { val: "Listing is still valid".to_string() });
+    }
+
+    Ok(invalidate_listing(deps.storage, &listing, vec![])?
+        .add_attribute("method", "try_invalidate_listing"))
 }
 
 pub fn try_edit_listing(
@@ -138,18 +391,22 @@ pub fn try_edit_listing(
     new_amount: u128,
 ) -> Result<Response, ContractError> {
     // Create a unique key for the listing using the collection name and token ID
-    let key = format!("{}:{}", collection, token_id);
-    let mut listing = LISTINGS.load(deps.storage, &key)?;
+    let key = (collection.as_str(), token_id.as_str());
+    let mut listing = listings().load(deps.storage, key)?;
 
     if listing.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
+    if matches!(listing.kind, ListingKind::Auction(_) | ListingKind::Dutch(_)) {
+        return Err(ContractError::AuctionListing {});
+    }
+
     // Update the listing's price to the new amount in uLuna
     listing.price = Some(new_amount);
 
     // Save the updated listing data
-    LISTINGS.save(deps.storage, &key, &listing)?;
+    save_listing(deps.storage, &listing)?;
 
     Ok(Response::new().add_attribute("method", "try_edit_listing"))
 }
@@ -160,37 +417,37 @@ pub fn try_delist_token(
     collection_name: String,
     token_id: String,
 ) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
     // Create a unique key for the listing using the collection name and token ID
-    let key = format!("{}:{}", collection_name, token_id);
-    let listing = LISTINGS.load(deps.storage, &key)
+    let key = (collection_name.as_str(), token_id.as_str());
+    let listing = listings().load(deps.storage, key)
     .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
 
     if listing.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
+    // Sellers can only cancel an auction before it receives a bid
+    if let ListingKind::Auction(Auction { highest_bid: Some(_), .. }) = listing.kind {
+        return Err(ContractError::CustomError { val: "Auction has bids".to_string() });
+    }
+
     // Retrieve the contract address for the given collection_name
     let collection = APPROVED_COLLECTIONS.load(deps.storage, &collection_name)
         .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
     
-    // Create a CosmosMsg for transferring the NFT
-    let transfer_msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::TransferNft {
-        recipient: listing.owner.to_string(),
-        token_id: token_id.clone(),
-    };    
-    let cosmos_msg = cosmwasm_std::CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
-        contract_addr: collection.contract_addr.to_string(),
-        msg: to_json_binary(&transfer_msg)?,
-        funds: vec![],
-    });
+    // Escrowed NFTs go back to the seller, approval listings never held it
+    let return_msgs = return_listed_nft(&listing, &collection)?;
 
     // Remove the listing
-    LISTINGS.remove(deps.storage, &key);
+    remove_listing(deps.storage, &listing)?;
 
     // Return a Response with the listing_data attribute
     Ok(Response::new()
         .add_attribute("method", "try_delist_token")
-        .add_message(cosmos_msg))
+        .add_messages(return_msgs)
+        .add_messages(refund_bids(&listing, None, &denom)))
 }
 
 pub fn try_remove_listing(
@@ -199,62 +456,69 @@ pub fn try_remove_listing(
     collection: String,
     token_id: String,
 ) -> Result<Response, ContractError> {
-    let state = STATE.load(deps.storage)?;
-    if info.sender != state.owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    let denom = payment_denom(deps.storage)?;
+
+    assert_role(deps.storage, &info.sender, Role::Moderator)?;
 
     // Create a unique key for the listing using the collection name and token ID
-    let key = format!("{}:{}", collection, token_id);
-    let listing = LISTINGS.load(deps.storage, &key)
+    let key = (collection.as_str(), token_id.as_str());
+    let listing = listings().load(deps.storage, key)
         .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
 
     // Retrieve the contract address for the given collection
     let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
         .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
 
-    // Create a CosmosMsg for transferring the NFT back to the owner
-    let transfer_msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::TransferNft {
-        recipient: listing.owner.to_string(),
-        token_id: token_id.clone(),
-    };
-    let cosmos_msg = cosmwasm_std::CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
-        contract_addr: collection_data.contract_addr.to_string(),
-        msg: to_json_binary(&transfer_msg)?,
-        funds: vec![],
-    });
+    // Transfer an escrowed NFT back to the owner
+    let return_msgs = return_listed_nft(&listing, &collection_data)?;
 
     // Remove the listing
-    LISTINGS.remove(deps.storage, &key);
+    remove_listing(deps.storage, &listing)?;
 
     // Return a Response with the transfer message
     Ok(Response::new()
         .add_attribute("method", "try_remove_listing")
-        .add_message(cosmos_msg))
+        .add_messages(return_msgs)
+        .add_messages(refund_bids(&listing, None, &denom)))
 }
 
 pub fn try_place_bid(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     collection: String,
     token_id: String,
     amount: u128,
+    expires: Option<Expiration>,
 ) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
     // Create the correct key for the listing using the collection name and token ID
-    let listing_key = format!("{}:{}", &collection, &token_id);
+    let listing_key = (collection.as_str(), token_id.as_str());
     
     // Check if the token is listed for sale
-    let mut listing = LISTINGS.load(deps.storage, &listing_key).map_err(|_| ContractError::CustomError { val: "Failed to load listing".to_string() })?;
+    let mut listing = listings().load(deps.storage, listing_key).map_err(|_| ContractError::CustomError { val: "Failed to load listing".to_string() })?;
+
+    verify_authorized_collection(deps.as_ref(), &collection)?;
+
+    if listing.is_expired(&env.block) {
+        return Err(ContractError::ListingExpired {});
+    }
+    if is_expired(expires, &env.block) {
+        return Err(ContractError::BidExpired {});
+    }
     
     // Ensure the bidder is not the owner
     if listing.owner == info.sender {
         return Err(ContractError::CustomError { val: "Bidder is token owner".to_string() });
     }
+    if listing.reserved_for.as_ref().is_some_and(|buyer| buyer != &info.sender) {
+        return Err(ContractError::ReservedListing {});
+    }
 
     // Check if the sent funds match the listing price
     let expected_funds = vec![Coin {
-        denom: "uluna".to_string(),
+        denom: denom.clone(),
         amount: amount.into(),
     }];
     
@@ -262,27 +526,203 @@ pub fn try_place_bid(
         return Err(ContractError::CustomError { val: "Sent funds do not match bid amount".to_string() });
     }
 
+    // Auctions hold a single escrowed high bid instead of a list of offers
+    match &mut listing.kind {
+        ListingKind::Auction(auction) => {
+            let response = place_auction_bid(&env, &info.sender, auction, amount, &denom)?;
+            save_listing(deps.storage, &listing)?;
+            return Ok(response);
+        },
+        // Dutch auctions are bought at their current price
+        ListingKind::Dutch(_) => return Err(ContractError::AuctionListing {}),
+        ListingKind::FixedPrice => {},
+    }
+
     // Check if there's already a bid from the bidder for this token
-    // Check if there's already a bid from the bidder for this token
+    let mut response = Response::new().add_attribute("method", "try_place_bid");
     let existing_bid_index = listing.bids.as_ref().and_then(|bids| bids.iter().position(|bid| bid.bidder == info.sender.clone()));
     if let Some(index) = existing_bid_index {
-        // Remove the existing bid
-        listing.bids.as_mut().unwrap().remove(index);
+        // Remove the existing bid and refund its escrow
+        let replaced_bid = listing.bids.as_mut().unwrap().remove(index);
+        response = response.add_message(bank_send(&replaced_bid.bidder, replaced_bid.amount, &denom));
     }
 
     // Save the new bid
     let bid = Bid {
         bidder: info.sender.clone(),
         amount,
+        expires,
     };    
     listing.bids.get_or_insert_with(Vec::new).push(bid);
 
     // Save the updated listing
-    LISTINGS.save(deps.storage, &listing_key, &listing)?;
+    save_listing(deps.storage, &listing)?;
+
+    Ok(response)
+}
+
+fn place_auction_bid(
+    env: &Env,
+    bidder: &Addr,
+    auction: &mut Auction,
+    amount: u128,
+    denom: &str,
+) -> Result<Response, ContractError> {
+    let now = env.block.time;
+    if now < auction.start_time || now >= auction.end_time {
+        return Err(ContractError::AuctionNotActive {});
+    }
+
+    // Each bid must beat the current high bid by at least the minimum increment
+    let min_bid = match &auction.highest_bid {
+        Some(highest_bid) => highest_bid.amount + auction.min_increment.max(1),
+        None => 1,
+    };
+    if amount < min_bid {
+        return Err(ContractError::BidTooLow { min_bid });
+    }
+
+    let mut response = Response::new().add_attribute("method", "try_place_bid");
+
+    // Refund the outbid bidder straight away
+    if let Some(previous_bid) = auction.highest_bid.replace(Bid { bidder: bidder.clone(), amount, expires: None }) {
+        response = response.add_message(bank_send(&previous_bid.bidder, previous_bid.amount, denom));
+    }
+
+    // Anti-sniping: a late bid extends the auction
+    if auction.end_time.seconds() - now.seconds() < AUCTION_EXTENSION_SECONDS {
+        auction.end_time = now.plus_seconds(AUCTION_EXTENSION_SECONDS);
+    }
+
+    Ok(response
+        .add_attribute("highest_bid", amount.to_string())
+        .add_attribute("end_time", auction.end_time.seconds().to_string()))
+}
 
-    Ok(Response::new().add_attribute("method", "try_place_bid"))
+pub fn try_settle_auction(
+    deps: DepsMut,
+    env: Env,
+    collection: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let key = (collection.as_str(), token_id.as_str());
+    let listing = listings().load(deps.storage, key)
+        .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
+
+    let auction = match &listing.kind {
+        ListingKind::Auction(auction) => auction,
+        _ => return Err(ContractError::CustomError { val: "Listing is not an auction".to_string() }),
+    };
+
+    if env.block.time < auction.end_time {
+        return Err(ContractError::AuctionNotEnded {});
+    }
+
+    // An ended auction settles in a paused collection too, or its NFT and high bid would be stuck
+    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+
+    // The NFT goes to the winner if the reserve was met, otherwise back to the seller
+    let winning_bid = auction.highest_bid.clone()
+        .filter(|bid| bid.amount >= auction.reserve_price);
+    let recipient = winning_bid.as_ref().map_or(&listing.owner, |bid| &bid.bidder);
+
+    let transfer_nft_msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: token_id.clone(),
+    };
+    let response = Response::new()
+        .add_message(cosmwasm_std::CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
+            contract_addr: collection_data.contract_addr.to_string(),
+            msg: to_json_binary(&transfer_nft_msg)?,
+            funds: vec![],
+        }))
+        .add_attribute("method", "try_settle_auction")
+        .add_attribute("recipient", recipient.to_string());
+
+    remove_listing(deps.storage, &listing)?;
+
+    match winning_bid {
+        Some(bid) => {
+            let proceeds = sale_proceeds(deps.as_ref(), &collection_data, &token_id, bid.amount)?;
+            record_sale(deps.storage, Sale {
+                id: 0, // Assigned by record_sale
+                collection,
+                token_id: token_id.clone(),
+                seller: listing.owner.clone(),
+                buyer: bid.bidder.clone(),
+                price: bid.amount,
+                kind: SaleKind::Auction,
+                time: env.block.time,
+            })?;
+            add_sale_proceeds(deps.storage, response.add_attribute("price", bid.amount.to_string()), &listing.owner, proceeds)
+        },
+        // Reserve not met: refund the highest bidder, if any
+        None => Ok(response.add_messages(refund_bids(&listing, None, &denom))),
+    }
+}
+
+
+pub fn try_remove_expired_bids(
+    deps: DepsMut,
+    env: Env,
+    collection: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let listing_key = (collection.as_str(), token_id.as_str());
+    let mut listing = listings().load(deps.storage, listing_key)
+        .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
+
+    // Anyone can clear expired bids, the escrow always goes back to the bidder
+    let (expired, active): (Vec<Bid>, Vec<Bid>) = listing.bids.take().unwrap_or_default()
+        .into_iter()
+        .partition(|bid| bid.is_expired(&env.block));
+    if expired.is_empty() {
+        return Err(ContractError::CustomError { val: "No expired bids".to_string() });
+    }
+    listing.bids = Some(active);
+
+    save_listing(deps.storage, &listing)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_expired_bids")
+        .add_attribute("removed", expired.len().to_string())
+        .add_messages(expired.iter().map(|bid| bank_send(&bid.bidder, bid.amount, &denom))))
 }
 
+pub fn try_return_expired_listing(
+    deps: DepsMut,
+    env: Env,
+    collection: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let key = (collection.as_str(), token_id.as_str());
+    let listing = listings().load(deps.storage, key)
+        .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
+
+    if !listing.is_expired(&env.block) {
+        return Err(ContractError::ListingNotExpired {});
+    }
+
+    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+
+    // Send an escrowed NFT back to the seller and refund every bid
+    let return_msgs = return_listed_nft(&listing, &collection_data)?;
+
+    remove_listing(deps.storage, &listing)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_return_expired_listing")
+        .add_messages(return_msgs)
+        .add_messages(refund_bids(&listing, None, &denom)))
+}
 
 pub fn try_remove_bid(
     deps: DepsMut,
@@ -290,11 +730,18 @@ pub fn try_remove_bid(
     collection: String,
     token_id: String,
 ) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
     // Check if the token is still listed for sale
-    let listing_key = format!("{}:{}", collection, token_id);
-    let mut listing = LISTINGS.load(deps.storage, &listing_key)
+    let listing_key = (collection.as_str(), token_id.as_str());
+    let mut listing = listings().load(deps.storage, listing_key)
         .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
 
+    // Auction bids stay escrowed until they are outbid or the auction settles
+    if let ListingKind::Auction(_) = listing.kind {
+        return Err(ContractError::AuctionListing {});
+    }
+
     // Check if there are any bids
     let bids = listing.bids.as_mut().ok_or(ContractError::CustomError { val: "No bids".to_string() })?;
 
@@ -309,7 +756,7 @@ pub fn try_remove_bid(
     let cosmos_msg = cosmwasm_std::CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
         to_address: bid.bidder.to_string(),
         amount: vec![Coin {
-            denom: "uluna".to_string(),
+            denom: denom.clone(),
             amount: bid.amount.into(),
         }],
     });
@@ -318,7 +765,7 @@ pub fn try_remove_bid(
     bids.remove(bid_index);
 
     // Save the updated listing
-    LISTINGS.save(deps.storage, &listing_key, &listing)?;
+    save_listing(deps.storage, &listing)?;
 
     Ok(Response::new()
         .add_attribute("method", "try_remove_bid")
@@ -327,7 +774,7 @@ pub fn try_remove_bid(
 
 pub fn try_accept_bid(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     collection: String,
     token_id: String,
@@ -337,10 +784,10 @@ pub fn try_accept_bid(
     let bidder_addr = deps.api.addr_validate(&bidder)?;
 
     // Create a unique key for the listing using the collection name and token ID
-    let listing_key = format!("{}:{}", &collection, &token_id);
+    let listing_key = (collection.as_str(), token_id.as_str());
     
     // Load the listing
-    let mut listing = LISTINGS.load(deps.storage, &listing_key)
+    let listing = listings().load(deps.storage, listing_key)
     .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
     
     // Verify the sender is the owner of the listing
@@ -348,8 +795,13 @@ pub fn try_accept_bid(
         return Err(ContractError::Unauthorized {});
     }
 
+    // Auctions are settled through SettleAuction, Dutch auctions through BuyNow
+    if matches!(listing.kind, ListingKind::Auction(_) | ListingKind::Dutch(_)) {
+        return Err(ContractError::AuctionListing {});
+    }
+
     // Check if there are any bids
-    let bids = listing.bids.as_mut().ok_or(ContractError::CustomError { val: "No bids".to_string() })?;
+    let bids = listing.bids.as_ref().ok_or(ContractError::CustomError { val: "No bids".to_string() })?;
 
     // Find the bid in the listing's bids vector and check if it exists
     let bid_index = bids.iter().position(|bid| bid.bidder == bidder_addr)
@@ -357,16 +809,21 @@ pub fn try_accept_bid(
 
     // Get the bid
     let bid = &bids[bid_index];
-
-    // Get the bid amount as a Coin
-    let bid_amount = vec![Coin {
-        denom: "uluna".to_string(),
-        amount: bid.amount.into(),
-    }];
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {});
+    }
 
     // Fetch the correct contract address for the collection
-    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
-        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    let collection_data = load_active_collection(deps.storage, &collection)?;
+
+    if !is_listing_valid(deps.as_ref(), &env, &listing, &collection_data) {
+        return Ok(invalidate_listing(deps.storage, &listing, vec![])?
+            .add_attribute("method", "try_accept_bid"));
+    }
+
+    // Split the bid amount between the marketplace fee, the royalty recipient and the seller
+    let proceeds = sale_proceeds(deps.as_ref(), &collection_data, &token_id, bid.amount)?;
+    let denom = payment_denom(deps.storage)?;
 
     // Transfer NFT to bidder
     let transfer_nft_msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::TransferNft {
@@ -375,119 +832,1705 @@ pub fn try_accept_bid(
     };
 
     // Remove the listing
-    LISTINGS.remove(deps.storage, &listing_key);
-
-    Ok(Response::new()
-        .add_message(cosmwasm_std::CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
-            to_address: listing.owner.to_string(),
-            amount: bid_amount,
-        }))
+    remove_listing(deps.storage, &listing)?;
+    record_sale(deps.storage, Sale {
+        id: 0, // Assigned by record_sale
+        collection,
+        token_id,
+        seller: listing.owner.clone(),
+        buyer: bidder_addr.clone(),
+        price: bid.amount,
+        kind: SaleKind::Bid,
+        time: env.block.time,
+    })?;
+
+    let response = Response::new()
         .add_message(cosmwasm_std::CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
             contract_addr: collection_data.contract_addr.to_string(),  // Use the correct contract address here
             msg: to_json_binary(&transfer_nft_msg)?,
             funds: vec![],
         }))
-        .add_attribute("method", "try_accept_bid"))
+        .add_messages(refund_bids(&listing, Some(&bidder_addr), &denom))
+        .add_attribute("method", "try_accept_bid");
+
+    add_sale_proceeds(deps.storage, response, &listing.owner, proceeds)
 }
 
 pub fn try_buy_now(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     collection: String,
     token_id: String,
 ) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
     // Create a unique key for the listing using the collection name and token ID
-    let key = format!("{}:{}", collection, token_id);
-    let listing = LISTINGS.load(deps.storage, &key)
+    let key = (collection.as_str(), token_id.as_str());
+    let listing = listings().load(deps.storage, key)
         .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
 
-    // Get listing price as a Coin
-    let listing_price = vec![Coin {
-        denom: "uluna".to_string(),
-        amount: listing.price.unwrap().into(),
-    }];
-
-    // Check if the sent funds match the listing price
-    if listing_price != info.funds {
-        return Err(ContractError::CustomError { val: "Sent funds do not match listing price".to_string() });
-    }
-
     // Retrieve the contract address for the given collection_name
-    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
-        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    let collection_data = load_active_collection(deps.storage, &collection)?;
 
-    // Create a CosmosMsg to transfer the NFT to the buyer
-    let transfer_nft_msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::TransferNft {
-        recipient: info.sender.to_string(),
-        token_id: token_id.clone(),
-    };
+    // A stale approval listing is closed and the buyer's funds are returned
+    if !is_listing_valid(deps.as_ref(), &env, &listing, &collection_data) {
+        return Ok(invalidate_listing(deps.storage, &listing, vec![bank_send_funds(&info.sender, info.funds)])?
+            .add_attribute("method", "try_buy_now"));
+    }
 
-    // Remove the listing
-    LISTINGS.remove(deps.storage, &key);
-    
+    let price = current_price(&env, &info.sender, &listing)?;
+
+    let mut response = Response::new();
+    if let ListingKind::Dutch(_) = listing.kind {
+        // Accept at least the current price and refund the rest
+        let paid = paid_amount(&info.funds, &denom)?;
+        if paid < price {
+            return Err(ContractError::CustomError { val: "Sent funds do not match listing price".to_string() });
+        }
+        if paid > price {
+            response = response.add_message(bank_send(&info.sender, paid - price, &denom));
+        }
+        response = response.add_attribute("price", price.to_string());
+    } else {
+        // Get listing price as a Coin
+        let listing_price = vec![Coin {
+            denom: denom.clone(),
+            amount: price.into(),
+        }];
+
+        // Check if the sent funds match the listing price
+        if listing_price != info.funds {
+            return Err(ContractError::CustomError { val: "Sent funds do not match listing price".to_string() });
+        }
+    }
 
-    Ok(Response::new()
-        .add_message(cosmwasm_std::CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
-            to_address: listing.owner.to_string(),
-            amount: info.funds,
-        }))
-        .add_message(cosmwasm_std::CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
-            contract_addr: collection_data.contract_addr.to_string(),
-            msg: to_json_binary(&transfer_nft_msg)?,
-            funds: vec![],
-        }))
-        .add_attribute("method", "try_buy_now"))
+    purchase_listing(deps, response.add_attribute("method", "try_buy_now"), &listing, &collection_data, &info.sender, price, &env.block)
 }
 
-
-
-pub fn try_add_approved_collection(
-    deps: DepsMut,
+/// Buys several listings with one funds attachment, refunding whatever is left over
+pub fn try_batch_buy(
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    collection: Collection,
+    items: Vec<BatchBuyItem>,
+    skip_unavailable: bool,
 ) -> Result<Response, ContractError> {
-    let state = STATE.load(deps.storage)?;
+    let denom = payment_denom(deps.storage)?;
 
-    if info.sender != state.owner {
-        return Err(ContractError::Unauthorized {});
+    if items.is_empty() || items.len() > MAX_BATCH_ITEMS {
+        return Err(ContractError::CustomError { val: format!("Batch needs between 1 and {} items", MAX_BATCH_ITEMS) });
     }
-    
-    // Check if the collection name already exists
-    if APPROVED_COLLECTIONS.load(deps.storage, &collection.name).is_ok() {
-        return Err(ContractError::CustomError { val: "Collection name already exists".to_string() });
+
+    let mut remaining = paid_amount(&info.funds, &denom)?;
+    let mut bought = 0;
+    let mut response = Response::new().add_attribute("method", "try_batch_buy");
+
+    for item in items {
+        match batch_purchase(deps.as_ref(), &env, &info.sender, &item, remaining) {
+            Ok((listing, collection_data, price)) => {
+                remaining -= price;
+                bought += 1;
+                response = response.add_attribute("bought", &item.token_id);
+                response = purchase_listing(deps.branch(), response, &listing, &collection_data, &info.sender, price, &env.block)?;
+            },
+            Err(_) if skip_unavailable => response = response.add_attribute("skipped", item.token_id),
+            Err(err) => return Err(err),
+        }
     }
-    
-    APPROVED_COLLECTIONS.save(deps.storage, &collection.name, &collection)?;
-    Ok(Response::new().add_attribute("method", "try_add_approved_collection"))
+
+    if remaining > 0 {
+        response = response.add_message(bank_send(&info.sender, remaining, &denom));
+    }
+
+    Ok(response
+        .add_attribute("bought_count", bought.to_string())
+        .add_attribute("refund", remaining.to_string()))
 }
 
-fn verify_authorized_collection(
+/// Checks that one item of a batch can be bought within its max price and the funds left
+fn batch_purchase(
     deps: Deps,
-    collection: &str,
-) -> Result<(), ContractError> {
-    // Try to load the collection from APPROVED_COLLECTIONS using the collection_name
-    match APPROVED_COLLECTIONS.load(deps.storage, collection) {
-        // If the collection is found, it's authorized.
-        Ok(_) => Ok(()),
-        // If the collection is not found, return an error.
+    env: &Env,
+    buyer: &Addr,
+    item: &BatchBuyItem,
+    remaining: u128,
+) -> Result<(Listing, Collection, u128), ContractError> {
+    let listing = listings().load(deps.storage, (&item.collection, &item.token_id))
+        .map_err(|_| ContractError::CustomError { val: "Listing not found".to_string() })?;
+    let collection_data = load_active_collection(deps.storage, &item.collection)?;
+
+    if !is_listing_valid(deps, env, &listing, &collection_data) {
+        return Err(ContractError::NotApproved {});
+    }
+
+    let price = current_price(env, buyer, &listing)?;
+    if price > item.max_price {
+        return Err(ContractError::CustomError { val: format!("Price of {} is above its max price", item.token_id) });
+    }
+    if price > remaining {
+        return Err(ContractError::CustomError { val: "Sent funds do not cover the batch".to_string() });
+    }
+
+    Ok((listing, collection_data, price))
+}
+
+/// Price `buyer` would pay for a listing right now
+fn current_price(env: &Env, buyer: &Addr, listing: &Listing) -> Result<u128, ContractError> {
+    if listing.is_expired(&env.block) {
+        return Err(ContractError::ListingExpired {});
+    }
+    if listing.reserved_for.as_ref().is_some_and(|reserved| reserved != buyer) {
+        return Err(ContractError::ReservedListing {});
+    }
+
+    match &listing.kind {
+        ListingKind::FixedPrice => listing.price.ok_or(ContractError::TokenNotForSale {}),
+        ListingKind::Dutch(dutch) => {
+            if env.block.time < dutch.start_time {
+                return Err(ContractError::AuctionNotActive {});
+            }
+            Ok(dutch.price_at(env.block.time))
+        },
+        ListingKind::Auction(_) => Err(ContractError::AuctionListing {}),
+    }
+}
+
+/// Closes a listing sold to `buyer` at `price` and pays out the proceeds
+fn purchase_listing(
+    deps: DepsMut,
+    response: Response,
+    listing: &Listing,
+    collection: &Collection,
+    buyer: &Addr,
+    price: u128,
+    block: &BlockInfo,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    // Split the sale price between the marketplace fee, the royalty recipient and the seller
+    let proceeds = sale_proceeds(deps.as_ref(), collection, &listing.token_id, price)?;
+
+    // Remove the listing
+    remove_listing(deps.storage, listing)?;
+    record_sale(deps.storage, Sale {
+        id: 0, // Assigned by record_sale
+        collection: listing.collection.clone(),
+        token_id: listing.token_id.clone(),
+        seller: listing.owner.clone(),
+        buyer: buyer.clone(),
+        price,
+        kind: SaleKind::BuyNow,
+        time: block.time,
+    })?;
+
+    let response = response
+        .add_message(transfer_nft_msg(collection, buyer, &listing.token_id)?)
+        .add_messages(refund_bids(listing, None, &denom));
+
+    add_sale_proceeds(deps.storage, response, &listing.owner, proceeds)
+}
+
+pub fn try_create_bundle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    items: Vec<TokenRef>,
+    price: Option<u128>,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    if items.len() < 2 || items.len() > MAX_BUNDLE_ITEMS {
+        return Err(ContractError::InvalidBundle { max: MAX_BUNDLE_ITEMS });
+    }
+    if has_duplicates(&items) {
+        return Err(ContractError::InvalidBundle { max: MAX_BUNDLE_ITEMS });
+    }
+    if price == Some(0) {
+        return Err(ContractError::InvalidPrice {});
+    }
+    if is_expired(expires, &env.block) {
+        return Err(ContractError::ListingExpired {});
+    }
+
+    // Every item is pulled into escrow with the approval the seller granted beforehand
+    let mut bundle_items = vec![];
+    let mut response = Response::new().add_attribute("method", "try_create_bundle");
+    for item in items {
+        verify_authorized_collection(deps.as_ref(), &item.collection)?;
+        let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &item.collection)?;
+
+        only_owner(deps.as_ref(), &info, &collection_data.contract_addr, item.token_id.clone())?;
+        if !is_approved(deps.as_ref(), &env, &collection_data, &info.sender, &item.token_id) {
+            return Err(ContractError::NotApproved {});
+        }
+
+        response = response.add_message(transfer_nft_msg(&collection_data, &env.contract.address, &item.token_id)?);
+        bundle_items.push(BundleItem {
+            token_uri: query_token_uri(deps.as_ref(), &collection_data, &item.token_id)?,
+            collection: item.collection,
+            token_id: item.token_id,
+        });
+    }
+
+    let id = BUNDLE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    BUNDLE_COUNT.save(deps.storage, &id)?;
+
+    let bundle = Bundle {
+        id,
+        owner: info.sender,
+        items: bundle_items,
+        price,
+        bids: vec![],
+        expires,
+    };
+    save_bundle(deps.storage, &bundle)?;
+
+    Ok(response.add_attribute("bundle_id", id.to_string()))
+}
+
+pub fn try_cancel_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let bundle = load_bundle(deps.storage, bundle_id)?;
+    if bundle.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "try_cancel_bundle")
+        .add_attribute("bundle_id", bundle_id.to_string())
+        .add_messages(close_bundle(deps.storage, &bundle, &denom)?))
+}
+
+/// Removes a bundle, returning every item to the seller and refunding every bid
+fn close_bundle(storage: &mut dyn Storage, bundle: &Bundle, denom: &str) -> StdResult<Vec<CosmosMsg>> {
+    remove_bundle(storage, bundle);
+
+    let mut msgs = vec![];
+    for item in &bundle.items {
+        let collection_data = APPROVED_COLLECTIONS.load(storage, &item.collection)?;
+        msgs.push(transfer_nft_msg(&collection_data, &bundle.owner, &item.token_id)?);
+    }
+    msgs.extend(bundle.bids.iter().map(|bid| bank_send(&bid.bidder, bid.amount, denom)));
+
+    Ok(msgs)
+}
+
+pub fn try_buy_bundle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let bundle = load_bundle(deps.storage, bundle_id)?;
+    if bundle.is_expired(&env.block) {
+        return Err(ContractError::ListingExpired {});
+    }
+
+    let price = bundle.price.ok_or(ContractError::TokenNotForSale {})?;
+    let expected_funds = vec![Coin {
+        denom: denom.clone(),
+        amount: price.into(),
+    }];
+    if info.funds != expected_funds {
+        return Err(ContractError::CustomError { val: "Sent funds do not match listing price".to_string() });
+    }
+
+    let response = Response::new()
+        .add_attribute("method", "try_buy_bundle")
+        .add_attribute("bundle_id", bundle_id.to_string())
+        .add_messages(bundle.bids.iter().map(|bid| bank_send(&bid.bidder, bid.amount, &denom)));
+
+    settle_bundle(deps, response, &bundle, &info.sender, price, &env.block)
+}
+
+pub fn try_place_bundle_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bundle_id: u64,
+    amount: u128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let mut bundle = load_bundle(deps.storage, bundle_id)?;
+    if bundle.is_expired(&env.block) {
+        return Err(ContractError::ListingExpired {});
+    }
+    if is_expired(expires, &env.block) {
+        return Err(ContractError::BidExpired {});
+    }
+    if bundle.owner == info.sender {
+        return Err(ContractError::CustomError { val: "Bidder is token owner".to_string() });
+    }
+
+    let expected_funds = vec![Coin {
+        denom: denom.clone(),
+        amount: amount.into(),
+    }];
+    if amount == 0 || info.funds != expected_funds {
+        return Err(ContractError::CustomError { val: "Sent funds do not match bid amount".to_string() });
+    }
+
+    // A new bid replaces the bidder's previous one
+    let mut response = Response::new()
+        .add_attribute("method", "try_place_bundle_bid")
+        .add_attribute("bundle_id", bundle_id.to_string());
+    if let Some(index) = bundle.bids.iter().position(|bid| bid.bidder == info.sender) {
+        let replaced_bid = bundle.bids.remove(index);
+        response = response.add_message(bank_send(&replaced_bid.bidder, replaced_bid.amount, &denom));
+    }
+
+    bundle.bids.push(Bid {
+        bidder: info.sender,
+        amount,
+        expires,
+    });
+    BUNDLES.save(deps.storage, bundle_id, &bundle)?;
+
+    Ok(response)
+}
+
+pub fn try_remove_bundle_bid(
+    deps: DepsMut,
+    info: MessageInfo,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let mut bundle = load_bundle(deps.storage, bundle_id)?;
+
+    let bid_index = bundle.bids.iter().position(|bid| bid.bidder == info.sender)
+        .ok_or(ContractError::CustomError { val: "Bid does not exist".to_string() })?;
+    let bid = bundle.bids.remove(bid_index);
+    BUNDLES.save(deps.storage, bundle_id, &bundle)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_bundle_bid")
+        .add_message(bank_send(&bid.bidder, bid.amount, &denom)))
+}
+
+pub fn try_accept_bundle_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bundle_id: u64,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let bidder_addr = deps.api.addr_validate(&bidder)?;
+    let bundle = load_bundle(deps.storage, bundle_id)?;
+    if bundle.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let bid = bundle.bids.iter().find(|bid| bid.bidder == bidder_addr)
+        .ok_or(ContractError::CustomError { val: "Bid does not exist".to_string() })?;
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {});
+    }
+
+    let response = Response::new()
+        .add_attribute("method", "try_accept_bundle_bid")
+        .add_attribute("bundle_id", bundle_id.to_string())
+        .add_messages(bundle.bids.iter()
+            .filter(|other| other.bidder != bidder_addr)
+            .map(|other| bank_send(&other.bidder, other.amount, &denom)));
+
+    settle_bundle(deps, response, &bundle, &bidder_addr, bid.amount, &env.block)
+}
+
+fn load_bundle(storage: &dyn Storage, bundle_id: u64) -> Result<Bundle, ContractError> {
+    BUNDLES.load(storage, bundle_id)
+        .map_err(|_| ContractError::CustomError { val: "Bundle not found".to_string() })
+}
+
+/// Closes a bundle, transferring every item to the buyer in the same transaction and paying out per item
+fn settle_bundle(
+    deps: DepsMut,
+    mut response: Response,
+    bundle: &Bundle,
+    buyer: &Addr,
+    price: u128,
+    block: &BlockInfo,
+) -> Result<Response, ContractError> {
+    remove_bundle(deps.storage, bundle);
+
+    // Fees and royalties are charged on an even share of the price, the remainder goes with the first item
+    let count = bundle.items.len() as u128;
+    let mut proceeds = vec![];
+    for (index, item) in bundle.items.iter().enumerate() {
+        let share = price / count + if index == 0 { price % count } else { 0 };
+        let collection_data = load_active_collection(deps.storage, &item.collection)?;
+        proceeds.push(sale_proceeds(deps.as_ref(), &collection_data, &item.token_id, share)?);
+        record_sale(deps.storage, Sale {
+            id: 0, // Assigned by record_sale
+            collection: item.collection.clone(),
+            token_id: item.token_id.clone(),
+            seller: bundle.owner.clone(),
+            buyer: buyer.clone(),
+            price: share,
+            kind: SaleKind::Bundle,
+            time: block.time,
+        })?;
+        response = response.add_message(transfer_nft_msg(&collection_data, buyer, &item.token_id)?);
+    }
+
+    for item_proceeds in proceeds {
+        response = add_sale_proceeds(deps.storage, response, &bundle.owner, item_proceeds)?;
+    }
+
+    Ok(response)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_propose_trade(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    counterparty: String,
+    offered: Vec<TokenRef>,
+    requested: Vec<TokenRef>,
+    requested_funds: u128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let counterparty = deps.api.addr_validate(&counterparty)?;
+    let offered_funds = paid_amount(&info.funds, &denom)?;
+
+    // Both sides have to give something, and no token may appear twice
+    if counterparty == info.sender
+        || (offered.is_empty() && offered_funds == 0)
+        || (requested.is_empty() && requested_funds == 0)
+        || offered.len() > MAX_TRADE_ITEMS
+        || requested.len() > MAX_TRADE_ITEMS
+        || has_duplicates(&offered)
+        || has_duplicates(&requested)
+    {
+        return Err(ContractError::InvalidTrade {});
+    }
+    if is_expired(expires, &env.block) {
+        return Err(ContractError::OfferExpired {});
+    }
+
+    for item in &requested {
+        verify_authorized_collection(deps.as_ref(), &item.collection)?;
+    }
+
+    // The maker's NFTs are pulled into escrow with the approval granted beforehand
+    let mut response = Response::new().add_attribute("method", "try_propose_trade");
+    for item in &offered {
+        verify_authorized_collection(deps.as_ref(), &item.collection)?;
+        let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &item.collection)?;
+
+        only_owner(deps.as_ref(), &info, &collection_data.contract_addr, item.token_id.clone())?;
+        if !is_approved(deps.as_ref(), &env, &collection_data, &info.sender, &item.token_id) {
+            return Err(ContractError::NotApproved {});
+        }
+        response = response.add_message(transfer_nft_msg(&collection_data, &env.contract.address, &item.token_id)?);
+    }
+
+    let id = TRADE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    TRADE_COUNT.save(deps.storage, &id)?;
+
+    let trade = Trade {
+        id,
+        maker: info.sender,
+        counterparty,
+        offered,
+        offered_funds,
+        requested,
+        requested_funds,
+        expires,
+    };
+    save_trade(deps.storage, &trade)?;
+
+    Ok(response
+        .add_attribute("trade_id", id.to_string())
+        .add_attribute("counterparty", trade.counterparty))
+}
+
+pub fn try_accept_trade(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    trade_id: u64,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let trade = load_trade(deps.storage, trade_id)?;
+
+    if trade.counterparty != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if trade.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired {});
+    }
+    if paid_amount(&info.funds, &denom)? != trade.requested_funds {
+        return Err(ContractError::CustomError { val: "Sent funds do not match requested funds".to_string() });
+    }
+
+    remove_trade(deps.storage, &trade);
+
+    let mut response = Response::new()
+        .add_attribute("method", "try_accept_trade")
+        .add_attribute("trade_id", trade_id.to_string());
+
+    // The counterparty's NFTs move straight to the maker through the Marketplace's approval
+    for item in &trade.requested {
+        let collection_data = load_active_collection(deps.storage, &item.collection)?;
+        only_owner(deps.as_ref(), &info, &collection_data.contract_addr, item.token_id.clone())?;
+        if !is_approved(deps.as_ref(), &env, &collection_data, &info.sender, &item.token_id) {
+            return Err(ContractError::NotApproved {});
+        }
+        response = response.add_message(transfer_nft_msg(&collection_data, &trade.maker, &item.token_id)?);
+    }
+
+    // Release the maker's escrow to the counterparty
+    for item in &trade.offered {
+        let collection_data = load_active_collection(deps.storage, &item.collection)?;
+        response = response.add_message(transfer_nft_msg(&collection_data, &trade.counterparty, &item.token_id)?);
+    }
+    if trade.offered_funds > 0 {
+        response = response.add_message(bank_send(&trade.counterparty, trade.offered_funds, &denom));
+    }
+    if trade.requested_funds > 0 {
+        response = response.add_message(bank_send(&trade.maker, trade.requested_funds, &denom));
+    }
+
+    Ok(response)
+}
+
+pub fn try_cancel_trade(
+    deps: DepsMut,
+    info: MessageInfo,
+    trade_id: u64,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let trade = load_trade(deps.storage, trade_id)?;
+
+    // Either side can call the trade off
+    if trade.maker != info.sender && trade.counterparty != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "try_cancel_trade")
+        .add_attribute("trade_id", trade_id.to_string())
+        .add_messages(close_trade(deps.storage, &trade, &denom)?))
+}
+
+/// Removes a trade, returning the maker's escrowed NFTs and funds
+fn close_trade(storage: &mut dyn Storage, trade: &Trade, denom: &str) -> StdResult<Vec<CosmosMsg>> {
+    remove_trade(storage, trade);
+
+    let mut msgs = vec![];
+    for item in &trade.offered {
+        let collection_data = APPROVED_COLLECTIONS.load(storage, &item.collection)?;
+        msgs.push(transfer_nft_msg(&collection_data, &trade.maker, &item.token_id)?);
+    }
+    if trade.offered_funds > 0 {
+        msgs.push(bank_send(&trade.maker, trade.offered_funds, denom));
+    }
+
+    Ok(msgs)
+}
+
+fn load_trade(storage: &dyn Storage, trade_id: u64) -> Result<Trade, ContractError> {
+    TRADES.load(storage, trade_id)
+        .map_err(|_| ContractError::CustomError { val: "Trade not found".to_string() })
+}
+
+fn has_duplicates(items: &[TokenRef]) -> bool {
+    items.iter().enumerate().any(|(i, item)| items[..i].contains(item))
+}
+
+/// Amount of the payment denom attached to a message, which may be none at all
+fn paid_amount(funds: &[Coin], denom: &str) -> Result<u128, ContractError> {
+    match funds {
+        [] => Ok(0),
+        [coin] if coin.denom == denom => Ok(coin.amount.u128()),
+        _ => Err(ContractError::CustomError { val: format!("Only {} is accepted", denom) }),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_create_collection_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    filter: Option<TraitFilter>,
+    price: u128,
+    quantity: u32,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    verify_authorized_collection(deps.as_ref(), &collection)?;
+
+    if price == 0 || quantity == 0 {
+        return Err(ContractError::InvalidPrice {});
+    }
+    if is_expired(expires, &env.block) {
+        return Err(ContractError::OfferExpired {});
+    }
+
+    // The full amount for every token is escrowed up front
+    let total = price.checked_mul(quantity as u128).ok_or(ContractError::InvalidPrice {})?;
+    let expected_funds = vec![Coin {
+        denom: denom.clone(),
+        amount: total.into(),
+    }];
+    if info.funds != expected_funds {
+        return Err(ContractError::CustomError { val: "Sent funds do not match offer total".to_string() });
+    }
+
+    let id = COLLECTION_OFFER_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    COLLECTION_OFFER_COUNT.save(deps.storage, &id)?;
+
+    let offer = CollectionOffer {
+        id,
+        bidder: info.sender,
+        collection,
+        filter,
+        price,
+        quantity,
+        filled: 0,
+        expires,
+    };
+    save_collection_offer(deps.storage, &offer)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_create_collection_offer")
+        .add_attribute("offer_id", id.to_string())
+        .add_attribute("collection", offer.collection))
+}
+
+pub fn try_cancel_collection_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_id: u64,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let offer = COLLECTION_OFFERS.load(deps.storage, offer_id)
+        .map_err(|_| ContractError::OfferNotFound {})?;
+
+    if offer.bidder != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(Response::new()
+        .add_message(close_collection_offer(deps.storage, &offer, &denom))
+        .add_attribute("method", "try_cancel_collection_offer")
+        .add_attribute("offer_id", offer_id.to_string()))
+}
+
+/// Removes a collection offer and refunds the escrow of every unfilled token
+fn close_collection_offer(storage: &mut dyn Storage, offer: &CollectionOffer, denom: &str) -> CosmosMsg {
+    remove_collection_offer(storage, offer);
+    bank_send(&offer.bidder, offer.price * (offer.quantity - offer.filled) as u128, denom)
+}
+
+fn try_fill_collection_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_msg: Cw721ReceiveMsg,
+    offer_id: u64,
+) -> Result<Response, ContractError> {
+    let mut offer = COLLECTION_OFFERS.load(deps.storage, offer_id)
+        .map_err(|_| ContractError::OfferNotFound {})?;
+
+    if offer.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired {});
+    }
+
+    // Only the offer's collection can deliver tokens for it
+    let collection_data = load_active_collection(deps.storage, &offer.collection)?;
+    if collection_data.contract_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let seller = deps.api.addr_validate(&nft_msg.sender)?;
+    if seller == offer.bidder {
+        return Err(ContractError::CustomError { val: "Bidder is token owner".to_string() });
+    }
+
+    if let Some(filter) = &offer.filter {
+        verify_token_traits(deps.as_ref(), &collection_data, &nft_msg.token_id, filter)?;
+    }
+
+    let proceeds = sale_proceeds(deps.as_ref(), &collection_data, &nft_msg.token_id, offer.price)?;
+
+    // Each fill takes one token, the offer closes once every token is filled
+    offer.filled += 1;
+    if offer.filled >= offer.quantity {
+        remove_collection_offer(deps.storage, &offer);
+    } else {
+        COLLECTION_OFFERS.save(deps.storage, offer_id, &offer)?;
+    }
+
+    record_sale(deps.storage, Sale {
+        id: 0, // Assigned by record_sale
+        collection: offer.collection.clone(),
+        token_id: nft_msg.token_id.clone(),
+        seller: seller.clone(),
+        buyer: offer.bidder.clone(),
+        price: offer.price,
+        kind: SaleKind::CollectionOffer,
+        time: env.block.time,
+    })?;
+
+    let response = Response::new()
+        .add_message(transfer_nft_msg(&collection_data, &offer.bidder, &nft_msg.token_id)?)
+        .add_attribute("method", "try_fill_collection_offer")
+        .add_attribute("offer_id", offer_id.to_string())
+        .add_attribute("token_id", nft_msg.token_id)
+        .add_attribute("filled", offer.filled.to_string());
+
+    add_sale_proceeds(deps.storage, response, &seller, proceeds)
+}
+
+pub fn try_make_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    token_id: String,
+    amount: u128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    verify_authorized_collection(deps.as_ref(), &collection)?;
+    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)?;
+
+    if amount == 0 {
+        return Err(ContractError::InvalidPrice {});
+    }
+    if is_expired(expires, &env.block) {
+        return Err(ContractError::OfferExpired {});
+    }
+
+    // The token has to exist, and the bidder cannot already hold it
+    let owner = Cw721Contract::<Empty, Empty>(collection_data.contract_addr, PhantomData, PhantomData)
+        .owner_of(&deps.querier, token_id.clone(), false)?;
+    if owner.owner == info.sender {
+        return Err(ContractError::CustomError { val: "Bidder is token owner".to_string() });
+    }
+
+    let expected_funds = vec![Coin {
+        denom: denom.clone(),
+        amount: amount.into(),
+    }];
+    if info.funds != expected_funds {
+        return Err(ContractError::CustomError { val: "Sent funds do not match offer amount".to_string() });
+    }
+
+    // A new offer replaces the bidder's previous one on the same token
+    let mut response = Response::new()
+        .add_attribute("method", "try_make_offer")
+        .add_attribute("token_id", token_id.clone());
+    let key = (collection.as_str(), token_id.as_str(), &info.sender);
+    if let Some(replaced) = token_offers().may_load(deps.storage, key)? {
+        response = response.add_message(bank_send(&replaced.bidder, replaced.amount, &denom));
+    }
+
+    let offer = TokenOffer {
+        collection: collection.clone(),
+        token_id: token_id.clone(),
+        bidder: info.sender.clone(),
+        amount,
+        expires,
+    };
+    token_offers().save(deps.storage, key, &offer)?;
+
+    Ok(response)
+}
+
+pub fn try_cancel_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    let key = (collection.as_str(), token_id.as_str(), &info.sender);
+    let offer = token_offers().load(deps.storage, key)
+        .map_err(|_| ContractError::OfferNotFound {})?;
+
+    token_offers().remove(deps.storage, key)?;
+
+    Ok(Response::new()
+        .add_message(bank_send(&offer.bidder, offer.amount, &denom))
+        .add_attribute("method", "try_cancel_offer")
+        .add_attribute("token_id", token_id))
+}
+
+fn try_accept_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_msg: Cw721ReceiveMsg,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    // The sending contract identifies the collection
+    let collection_data = collection_by_addr(deps.storage, &info.sender)?;
+    let bidder_addr = deps.api.addr_validate(&bidder)?;
+
+    let key = (collection_data.name.as_str(), nft_msg.token_id.as_str(), &bidder_addr);
+    let offer = token_offers().load(deps.storage, key)
+        .map_err(|_| ContractError::OfferNotFound {})?;
+
+    if offer.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired {});
+    }
+
+    let seller = deps.api.addr_validate(&nft_msg.sender)?;
+    let proceeds = sale_proceeds(deps.as_ref(), &collection_data, &nft_msg.token_id, offer.amount)?;
+
+    token_offers().remove(deps.storage, key)?;
+    record_sale(deps.storage, Sale {
+        id: 0, // Assigned by record_sale
+        collection: collection_data.name.clone(),
+        token_id: nft_msg.token_id.clone(),
+        seller: seller.clone(),
+        buyer: bidder_addr.clone(),
+        price: offer.amount,
+        kind: SaleKind::Offer,
+        time: env.block.time,
+    })?;
+
+    let response = Response::new()
+        .add_message(transfer_nft_msg(&collection_data, &bidder_addr, &nft_msg.token_id)?)
+        .add_attribute("method", "try_accept_offer")
+        .add_attribute("token_id", nft_msg.token_id)
+        .add_attribute("bidder", bidder_addr);
+
+    add_sale_proceeds(deps.storage, response, &seller, proceeds)
+}
+
+/// Checks a token's traits, as reported by its collection, against an offer filter
+fn verify_token_traits(
+    deps: Deps,
+    collection: &Collection,
+    token_id: &str,
+    filter: &TraitFilter,
+) -> Result<(), ContractError> {
+    let traits: TokenTraitsResponse = deps.querier.query_wasm_smart(
+        &collection.contract_addr,
+        &CollectionQueryMsg::TokenTraits { token_id: token_id.to_string() },
+    ).map_err(|e| ContractError::QueryError(e.to_string()))?;
+
+    let matches = |wanted: &Option<String>, actual: Option<&String>| {
+        wanted.as_ref().map_or(true, |wanted| Some(wanted) == actual)
+    };
+    if matches(&filter.base_id, Some(&traits.base_id))
+        && matches(&filter.rarity, traits.rarity.as_ref())
+        && matches(&filter.season_id, traits.season_id.as_ref())
+    {
+        Ok(())
+    } else {
+        Err(ContractError::OfferMismatch {})
+    }
+}
+
+fn transfer_nft_msg(
+    collection: &Collection,
+    recipient: &Addr,
+    token_id: &str,
+) -> StdResult<CosmosMsg> {
+    let transfer_msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: token_id.to_string(),
+    };
+    Ok(CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
+        contract_addr: collection.contract_addr.to_string(),
+        msg: to_json_binary(&transfer_msg)?,
+        funds: vec![],
+    }))
+}
+
+/// How the proceeds of a sale are split between the marketplace fee, the royalty recipient and the seller
+struct SaleProceeds {
+    fee: u128,
+    fee_collector: Option<Addr>,
+    royalty: Option<(Addr, u128)>,
+    seller_amount: u128,
+}
+
+fn sale_proceeds(
+    deps: Deps,
+    collection: &Collection,
+    token_id: &str,
+    price: u128,
+) -> Result<SaleProceeds, ContractError> {
+    let fee_config = FEE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let fee_bps = collection.fee_bps.unwrap_or(fee_config.fee_bps);
+    let fee = Uint128::from(price).multiply_ratio(fee_bps, MAX_FEE_BPS).u128();
+
+    // Royalties are taken from what remains after the marketplace fee
+    let royalty = query_royalty(deps, collection, token_id, price)?
+        .map(|(recipient, amount)| (recipient, amount.min(price - fee)));
+    let royalty_amount = royalty.as_ref().map_or(0, |(_, amount)| *amount);
+
+    Ok(SaleProceeds {
+        fee,
+        fee_collector: fee_config.fee_collector,
+        royalty,
+        seller_amount: price - fee - royalty_amount,
+    })
+}
+
+/// Queries the collection for cw2981 royalties and applies the collection's royalty cap
+fn query_royalty(
+    deps: Deps,
+    collection: &Collection,
+    token_id: &str,
+    price: u128,
+) -> Result<Option<(Addr, u128)>, ContractError> {
+    // Collections that don't implement cw2981 owe no royalties
+    let check: StdResult<CheckRoyaltiesResponse> = deps.querier.query_wasm_smart(
+        &collection.contract_addr,
+        &CollectionQueryMsg::Extension { msg: Cw2981QueryMsg::CheckRoyalties {} },
+    );
+    if !matches!(check, Ok(CheckRoyaltiesResponse { royalty_payments: true })) {
+        return Ok(None);
+    }
+
+    let royalty_info: RoyaltiesInfoResponse = deps.querier.query_wasm_smart(
+        &collection.contract_addr,
+        &CollectionQueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltyInfo {
+                token_id: token_id.to_string(),
+                sale_price: Uint128::from(price),
+            },
+        },
+    ).map_err(|e| ContractError::QueryError(e.to_string()))?;
+
+    if royalty_info.address.is_empty() || royalty_info.royalty_amount.is_zero() {
+        return Ok(None);
+    }
+    let recipient = deps.api.addr_validate(&royalty_info.address)?;
+
+    // Never pay out more than the collection's cap, or more than the sale price
+    let cap = collection.max_royalty_percentage
+        .map_or(price, |percentage| Uint128::from(price).multiply_ratio(percentage, 100u128).u128());
+    let amount = royalty_info.royalty_amount.u128().min(cap).min(price);
+
+    Ok(Some((recipient, amount)))
+}
+
+/// Adds the fee, royalty and seller payouts of a sale to the response and records the fee
+fn add_sale_proceeds(
+    storage: &mut dyn Storage,
+    mut response: Response,
+    seller: &Addr,
+    proceeds: SaleProceeds,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(storage)?;
+
+    if proceeds.fee > 0 {
+        let mut totals = FEE_TOTALS.may_load(storage)?.unwrap_or_default();
+        totals.collected += proceeds.fee;
+        match &proceeds.fee_collector {
+            Some(collector) => response = response.add_message(bank_send(collector, proceeds.fee, &denom)),
+            // Without a collector the fee stays in the contract until withdrawn
+            None => totals.accrued += proceeds.fee,
+        }
+        FEE_TOTALS.save(storage, &totals)?;
+    }
+
+    let royalty_amount = match proceeds.royalty {
+        Some((recipient, amount)) => {
+            if amount > 0 {
+                response = response.add_message(bank_send(&recipient, amount, &denom));
+            }
+            response = response.add_attribute("royalty_recipient", recipient);
+            amount
+        },
+        None => 0,
+    };
+    if proceeds.seller_amount > 0 {
+        response = response.add_message(bank_send(seller, proceeds.seller_amount, &denom));
+    }
+
+    Ok(response
+        .add_attribute("marketplace_fee", proceeds.fee.to_string())
+        .add_attribute("royalty_amount", royalty_amount.to_string())
+        .add_attribute("seller_amount", proceeds.seller_amount.to_string()))
+}
+
+/// Refunds every bid escrowed on a closing listing, except the bid being settled
+fn refund_bids(listing: &Listing, settled_bidder: Option<&Addr>, denom: &str) -> Vec<CosmosMsg> {
+    let auction_bid = match &listing.kind {
+        ListingKind::Auction(auction) => auction.highest_bid.as_ref(),
+        _ => None,
+    };
+
+    listing.bids.iter()
+        .flatten()
+        .chain(auction_bid)
+        .filter(|bid| Some(&bid.bidder) != settled_bidder)
+        .map(|bid| bank_send(&bid.bidder, bid.amount, denom))
+        .collect()
+}
+
+/// Returns an escrowed NFT to its seller, approval listings have nothing to return
+fn return_listed_nft(listing: &Listing, collection: &Collection) -> StdResult<Vec<CosmosMsg>> {
+    match listing.custody {
+        ListingCustody::Escrow => Ok(vec![transfer_nft_msg(collection, &listing.owner, &listing.token_id)?]),
+        ListingCustody::Approval => Ok(vec![]),
+    }
+}
+
+/// Escrowed listings are always valid, approval listings need the seller to still own and approve the token
+fn is_listing_valid(deps: Deps, env: &Env, listing: &Listing, collection: &Collection) -> bool {
+    if listing.custody == ListingCustody::Escrow {
+        return true;
+    }
+
+    let owner = Cw721Contract::<Empty, Empty>(collection.contract_addr.clone(), PhantomData, PhantomData)
+        .owner_of(&deps.querier, listing.token_id.clone(), false);
+    match owner {
+        Ok(owner) if owner.owner == listing.owner => is_approved(deps, env, collection, &listing.owner, &listing.token_id),
+        _ => false,
+    }
+}
+
+/// The Marketplace can move a token through either a per-token approval or an operator approval from its owner
+fn is_approved(deps: Deps, env: &Env, collection: &Collection, owner: &Addr, token_id: &str) -> bool {
+    let token_approval = deps.querier.query_wasm_smart::<ApprovalResponse>(
+        &collection.contract_addr,
+        &Cw721QueryMsg::Approval {
+            token_id: token_id.to_string(),
+            spender: env.contract.address.to_string(),
+            include_expired: Some(false),
+        },
+    );
+    if token_approval.is_ok() {
+        return true;
+    }
+
+    deps.querier
+        .query_wasm_smart::<OperatorResponse>(
+            &collection.contract_addr,
+            &Cw721QueryMsg::Operator {
+                owner: owner.to_string(),
+                operator: env.contract.address.to_string(),
+                include_expired: Some(false),
+            },
+        )
+        .is_ok()
+}
+
+/// Closes a listing that can no longer be sold and refunds everything escrowed against it
+fn invalidate_listing(
+    storage: &mut dyn Storage,
+    listing: &Listing,
+    refunds: Vec<CosmosMsg>,
+) -> StdResult<Response> {
+    let denom = payment_denom(storage)?;
+
+    remove_listing(storage, listing)?;
+
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_messages(refund_bids(listing, None, &denom))
+        .add_attribute("listing_invalidated", &listing.token_id))
+}
+
+fn query_token_uri(deps: Deps, collection: &Collection, token_id: &str) -> Result<String, ContractError> {
+    // The token uri is read from the collection rather than trusted from the seller
+    let nft_info: NftInfoResponse = deps.querier.query_wasm_smart(
+        &collection.contract_addr,
+        &Cw721QueryMsg::NftInfo { token_id: token_id.to_string() },
+    ).map_err(|e| ContractError::QueryError(e.to_string()))?;
+
+    Ok(nft_info.token_uri.unwrap_or_default())
+}
+
+fn bank_send_funds(to: &Addr, funds: Vec<Coin>) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: funds,
+    })
+}
+
+fn bank_send(to: &Addr, amount: u128, denom: &str) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: vec![Coin {
+            denom: denom.to_string(),
+            amount: amount.into(),
+        }],
+    })
+}
+
+
+
+pub fn try_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: cw_ownable::Action,
+) -> Result<Response, ContractError> {
+    let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+    Ok(Response::new()
+        .add_attribute("method", "try_update_ownership")
+        .add_attributes(ownership.into_attributes()))
+}
+
+pub fn try_grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, (role.as_str(), &address), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_grant_role")
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", address))
+}
+
+pub fn try_revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if !ROLES.has(deps.storage, (role.as_str(), &address)) {
+        return Err(ContractError::CustomError { val: "Address does not hold this role".to_string() });
+    }
+    ROLES.remove(deps.storage, (role.as_str(), &address));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_revoke_role")
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", address))
+}
+
+/// Guardians can only pause. Lifting a pause is left to the owner.
+pub fn try_update_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    scopes: Vec<PauseScope>,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    if paused {
+        assert_role(deps.storage, &info.sender, Role::Guardian)?;
+    } else {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+    }
+
+    if scopes.is_empty() {
+        return Err(ContractError::CustomError { val: "No pause scopes given".to_string() });
+    }
+
+    let mut pause = PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let mut response = Response::new()
+        .add_attribute("method", "try_update_pause")
+        .add_attribute("sender", info.sender.clone());
+    for scope in scopes {
+        pause.set(scope, paused);
+        response = response.add_event(
+            Event::new("marketplace_pause")
+                .add_attribute("scope", scope.as_str())
+                .add_attribute("paused", paused.to_string())
+                .add_attribute("sender", info.sender.clone()),
+        );
+    }
+    PAUSE_STATE.save(deps.storage, &pause)?;
+
+    Ok(response)
+}
+
+/// The owner implicitly holds every role
+fn assert_role(storage: &dyn Storage, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if cw_ownable::is_owner(storage, sender)? || ROLES.has(storage, (role.as_str(), sender)) {
+        return Ok(());
+    }
+
+    Err(ContractError::Unauthorized {})
+}
+
+pub fn try_add_approved_collection(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: Collection,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CollectionCurator)?;
+    
+    // Check if the collection name already exists
+    if APPROVED_COLLECTIONS.load(deps.storage, &collection.name).is_ok() {
+        return Err(ContractError::CustomError { val: "Collection name already exists".to_string() });
+    }
+
+    validate_collection(deps.as_ref(), &collection)?;
+    ensure_address_unapproved(deps.storage, &collection.contract_addr)?;
+    
+    save_collection(deps.storage, &collection)?;
+    Ok(Response::new().add_attribute("method", "try_add_approved_collection"))
+}
+
+fn validate_collection(deps: Deps, collection: &Collection) -> Result<(), ContractError> {
+    deps.api.addr_validate(collection.contract_addr.as_str())?;
+
+    if collection.name.is_empty() {
+        return Err(ContractError::CustomError { val: "Collection name is empty".to_string() });
+    }
+
+    if collection.max_royalty_percentage.is_some_and(|percentage| percentage > 100) {
+        return Err(ContractError::InvalidRoyaltyPercentage {});
+    }
+
+    if collection.fee_bps.is_some_and(|bps| bps > MAX_FEE_BPS) {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    Ok(())
+}
+
+pub fn try_remove_approved_collection(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CollectionCurator)?;
+
+    let collection = APPROVED_COLLECTIONS.load(deps.storage, &name)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+
+    // Pause the collection first and let its listings and offers wind down
+    if collection_in_use(deps.storage, &name) {
+        return Err(ContractError::CollectionInUse {});
+    }
+
+    remove_collection(deps.storage, &collection);
+
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_approved_collection")
+        .add_attribute("collection", name))
+}
+
+pub fn try_rename_collection(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    new_name: String,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CollectionCurator)?;
+
+    let mut collection = APPROVED_COLLECTIONS.load(deps.storage, &name)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    if new_name.is_empty() || APPROVED_COLLECTIONS.has(deps.storage, &new_name) {
+        return Err(ContractError::CustomError { val: "Collection name already exists".to_string() });
+    }
+
+    // Listings and offers are keyed by collection name, so they would be orphaned by a rename
+    if collection_in_use(deps.storage, &name) {
+        return Err(ContractError::CollectionInUse {});
+    }
+
+    // Totals carry over, past sales keep the name they were made under
+    if let Some(stats) = COLLECTION_STATS.may_load(deps.storage, &name)? {
+        COLLECTION_STATS.save(deps.storage, &new_name, &stats)?;
+        COLLECTION_STATS.remove(deps.storage, &name);
+    }
+
+    remove_collection(deps.storage, &collection);
+    collection.name = new_name.clone();
+    save_collection(deps.storage, &collection)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_rename_collection")
+        .add_attribute("collection", name)
+        .add_attribute("new_name", new_name))
+}
+
+pub fn try_update_collection_address(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    contract_addr: String,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CollectionCurator)?;
+
+    let mut collection = APPROVED_COLLECTIONS.load(deps.storage, &name)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+
+    // Escrowed NFTs can only be returned through the contract that holds them
+    if collection_in_use(deps.storage, &name) {
+        return Err(ContractError::CollectionInUse {});
+    }
+
+    let new_addr = deps.api.addr_validate(&contract_addr)?;
+    ensure_address_unapproved(deps.storage, &new_addr)?;
+    remove_collection(deps.storage, &collection);
+    collection.contract_addr = new_addr;
+    save_collection(deps.storage, &collection)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_update_collection_address")
+        .add_attribute("collection", name)
+        .add_attribute("contract_addr", contract_addr))
+}
+
+pub fn try_set_collection_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CollectionCurator)?;
+
+    let mut collection = APPROVED_COLLECTIONS.load(deps.storage, &name)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    collection.paused = paused;
+    APPROVED_COLLECTIONS.save(deps.storage, &name, &collection)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_collection_paused")
+        .add_attribute("collection", name)
+        .add_attribute("paused", paused.to_string()))
+}
+
+/// Whether any listing, offer, bundle or trade still references the collection
+fn collection_in_use(storage: &dyn Storage, name: &str) -> bool {
+    let order = cosmwasm_std::Order::Ascending;
+    listings().prefix(name).keys_raw(storage, None, None, order).next().is_some()
+        || token_offers().sub_prefix(name).keys_raw(storage, None, None, order).next().is_some()
+        || COLLECTION_OFFERS_BY_COLLECTION.prefix(name).keys_raw(storage, None, None, order).next().is_some()
+        || BUNDLES_BY_COLLECTION.prefix(name).keys_raw(storage, None, None, order).next().is_some()
+        || TRADES_BY_COLLECTION.prefix(name).keys_raw(storage, None, None, order).next().is_some()
+}
+
+/// Closes up to `limit` of the listings, offers, bundles and trades that reference a collection,
+/// returning every NFT and refunding every escrow. Bundles and trades are closed whole, including
+/// items from other collections.
+pub fn try_clear_collection(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if assert_role(deps.storage, &info.sender, Role::CollectionCurator).is_err() {
+        assert_role(deps.storage, &info.sender, Role::Moderator)?;
+    }
+
+    let denom = payment_denom(deps.storage)?;
+    let collection_data = APPROVED_COLLECTIONS.load(deps.storage, &name)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    let order = cosmwasm_std::Order::Ascending;
+    let mut remaining = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut msgs = vec![];
+
+    let closing_listings = listings()
+        .prefix(&name)
+        .range(deps.storage, None, None, order)
+        .take(remaining)
+        .map(|item| item.map(|(_, listing)| listing))
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= closing_listings.len();
+    for listing in &closing_listings {
+        remove_listing(deps.storage, listing)?;
+        msgs.extend(return_listed_nft(listing, &collection_data)?);
+        msgs.extend(refund_bids(listing, None, &denom));
+    }
+
+    let closing_offers = token_offers()
+        .sub_prefix(&name)
+        .range(deps.storage, None, None, order)
+        .take(remaining)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= closing_offers.len();
+    for offer in &closing_offers {
+        token_offers().remove(deps.storage, (&offer.collection, &offer.token_id, &offer.bidder))?;
+        msgs.push(bank_send(&offer.bidder, offer.amount, &denom));
+    }
+
+    let offer_ids = COLLECTION_OFFERS_BY_COLLECTION
+        .prefix(&name)
+        .keys(deps.storage, None, None, order)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= offer_ids.len();
+    for offer_id in offer_ids {
+        let offer = COLLECTION_OFFERS.load(deps.storage, offer_id)?;
+        msgs.push(close_collection_offer(deps.storage, &offer, &denom));
+    }
+
+    let bundle_ids = BUNDLES_BY_COLLECTION
+        .prefix(&name)
+        .keys(deps.storage, None, None, order)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= bundle_ids.len();
+    for bundle_id in bundle_ids {
+        let bundle = BUNDLES.load(deps.storage, bundle_id)?;
+        msgs.extend(close_bundle(deps.storage, &bundle, &denom)?);
+    }
+
+    let trade_ids = TRADES_BY_COLLECTION
+        .prefix(&name)
+        .keys(deps.storage, None, None, order)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    for trade_id in trade_ids {
+        let trade = TRADES.load(deps.storage, trade_id)?;
+        msgs.extend(close_trade(deps.storage, &trade, &denom)?);
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "try_clear_collection")
+        .add_attribute("collection", &name)
+        .add_attribute("in_use", collection_in_use(deps.storage, &name).to_string()))
+}
+
+pub fn try_set_royalty_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+    max_royalty_percentage: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CollectionCurator)?;
+
+    if max_royalty_percentage.is_some_and(|percentage| percentage > 100) {
+        return Err(ContractError::InvalidRoyaltyPercentage {});
+    }
+
+    let mut collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    collection_data.max_royalty_percentage = max_royalty_percentage;
+    APPROVED_COLLECTIONS.save(deps.storage, &collection, &collection_data)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_royalty_cap")
+        .add_attribute("collection", collection))
+}
+
+pub fn try_set_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_bps: u64,
+    fee_collector: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::FeeManager)?;
+
+    if fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    let fee_collector = fee_collector
+        .map(|collector| deps.api.addr_validate(&collector))
+        .transpose()?;
+    FEE_CONFIG.save(deps.storage, &FeeConfig { fee_bps, fee_collector })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_fee")
+        .add_attribute("fee_bps", fee_bps.to_string()))
+}
+
+pub fn try_set_collection_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+    fee_bps: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::FeeManager)?;
+
+    if fee_bps.is_some_and(|bps| bps > MAX_FEE_BPS) {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    let mut collection_data = APPROVED_COLLECTIONS.load(deps.storage, &collection)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    collection_data.fee_bps = fee_bps;
+    APPROVED_COLLECTIONS.save(deps.storage, &collection, &collection_data)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_collection_fee")
+        .add_attribute("collection", collection))
+}
+
+pub fn try_withdraw_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<u128>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let denom = payment_denom(deps.storage)?;
+
+    // The configured collector can pull fees that accrued before it was set
+    let fee_config = FEE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if fee_config.fee_collector.as_ref() != Some(&info.sender) {
+        assert_role(deps.storage, &info.sender, Role::FeeManager)?;
+    }
+
+    let mut totals = FEE_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    let amount = amount.unwrap_or(totals.accrued);
+    if amount == 0 || amount > totals.accrued {
+        return Err(ContractError::InsufficientFees {});
+    }
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender,
+    };
+
+    totals.accrued -= amount;
+    totals.withdrawn += amount;
+    FEE_TOTALS.save(deps.storage, &totals)?;
+
+    Ok(Response::new()
+        .add_message(bank_send(&recipient, amount, &denom))
+        .add_attribute("method", "try_withdraw_fees")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount.to_string()))
+}
+
+pub fn try_resolve_legacy_bid(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+    token_id: String,
+    bidder: String,
+    refund: bool,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let key = (collection.as_str(), token_id.as_str(), &bidder);
+    let bid = LEGACY_BIDS.may_load(deps.storage, key)?
+        .ok_or(ContractError::CustomError { val: "Legacy bid not found".to_string() })?;
+    LEGACY_BIDS.remove(deps.storage, key);
+
+    let mut response = Response::new()
+        .add_attribute("method", "try_resolve_legacy_bid")
+        .add_attribute("collection", collection)
+        .add_attribute("token_id", token_id)
+        .add_attribute("bidder", bidder.to_string())
+        .add_attribute("refunded", refund.to_string());
+    if refund {
+        let denom = payment_denom(deps.storage)?;
+        response = response.add_message(bank_send(&bid.bidder, bid.amount, &denom));
+    }
+
+    Ok(response)
+}
+
+/// Denom every price, bid, offer and fee is paid in
+fn payment_denom(storage: &dyn Storage) -> StdResult<String> {
+    Ok(PAYMENT_DENOM.may_load(storage)?.unwrap_or_else(|| DEFAULT_DENOM.to_string()))
+}
+
+/// `collection_by_addr` resolves a contract to one collection, so each address may only be approved once
+fn ensure_address_unapproved(storage: &dyn Storage, addr: &Addr) -> Result<(), ContractError> {
+    match COLLECTIONS_BY_ADDR.may_load(storage, addr)? {
+        Some(name) => Err(ContractError::DuplicateCollectionAddress { contract_addr: addr.to_string(), name }),
+        None => Ok(()),
+    }
+}
+
+/// Looks up the approved collection behind a cw721 contract address
+fn collection_by_addr(storage: &dyn Storage, addr: &Addr) -> Result<Collection, ContractError> {
+    let name = COLLECTIONS_BY_ADDR.may_load(storage, addr)?.ok_or(ContractError::Unauthorized {})?;
+    let collection = APPROVED_COLLECTIONS.load(storage, &name)?;
+    if collection.paused {
+        return Err(ContractError::CollectionPaused {});
+    }
+
+    Ok(collection)
+}
+
+/// Loads a collection that is approved and not paused
+fn load_active_collection(storage: &dyn Storage, name: &str) -> Result<Collection, ContractError> {
+    let collection = APPROVED_COLLECTIONS.load(storage, name)
+        .map_err(|_| ContractError::CustomError { val: "Collection not found".to_string() })?;
+    if collection.paused {
+        return Err(ContractError::CollectionPaused {});
+    }
+
+    Ok(collection)
+}
+
+fn verify_authorized_collection(
+    deps: Deps,
+    collection: &str,
+) -> Result<(), ContractError> {
+    // Try to load the collection from APPROVED_COLLECTIONS using the collection_name
+    match APPROVED_COLLECTIONS.load(deps.storage, collection) {
+        // A paused collection stays approved but takes no new activity
+        Ok(collection) if collection.paused => Err(ContractError::CollectionPaused {}),
+        // If the collection is found, it's authorized.
+        Ok(_) => Ok(()),
+        // If the collection is not found, return an error.
         Err(_) => Err(ContractError::CustomError { val: "Collection not authorized".to_string() }),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetListingDetails { collection, token_id } => to_json_binary(&query_listing(deps, collection, token_id)?),
-        QueryMsg::GetListings { start_after, limit } => to_json_binary(&query_listings(deps, start_after, limit)?),
+        QueryMsg::GetListings { start_after, limit, include_expired, reserved_for } => to_json_binary(&query_listings(deps, env, start_after, limit, include_expired.unwrap_or(false), reserved_for)?),
         QueryMsg::GetStateOwner {} => to_json_binary(&query_state_owner(deps)?),
-        QueryMsg::GetBidDetails { collection, token_id } => to_json_binary(&query_bid(deps, collection, token_id)?),
+        QueryMsg::GetDenom {} => to_json_binary(&payment_denom(deps.storage)?),
+        QueryMsg::PauseState {} => to_json_binary(&PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::Ownership {} => to_json_binary(&cw_ownable::get_ownership(deps.storage)?),
+        QueryMsg::Roles { address } => to_json_binary(&query_roles(deps, address)?),
+        QueryMsg::RoleMembers { role, start_after, limit } => to_json_binary(&query_role_members(deps, role, start_after, limit)?),
+        QueryMsg::ApprovedCollections { start_after, limit } => to_json_binary(&query_approved_collections(deps, start_after, limit)?),
+        QueryMsg::LegacyBids { start_after, limit } => to_json_binary(&query_legacy_bids(deps, start_after, limit)?),
+        QueryMsg::GetBidDetails { collection, token_id, include_expired } => to_json_binary(&query_bid(deps, env, collection, token_id, include_expired.unwrap_or(false))?),
+        QueryMsg::GetFees {} => to_json_binary(&query_fees(deps)?),
+        QueryMsg::GetDutchPrice { collection, token_id, time } => to_json_binary(&query_dutch_price(deps, env, collection, token_id, time)?),
+        QueryMsg::GetCollectionOffer { offer_id } => to_json_binary(&COLLECTION_OFFERS.load(deps.storage, offer_id)?),
+        QueryMsg::GetCollectionOffers { collection, start_after, limit, include_expired } => to_json_binary(&query_collection_offers(deps, env, collection, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::GetBundle { bundle_id } => to_json_binary(&BUNDLES.load(deps.storage, bundle_id)?),
+        QueryMsg::GetBundles { start_after, limit, include_expired } => to_json_binary(&query_bundles(deps, env, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::SearchListings { filter, sort_by, descending, start_after, limit } => {
+            to_json_binary(&query_search_listings(deps, env, filter.unwrap_or_default(), sort_by, descending.unwrap_or(false), start_after, limit)?)
+        },
+        QueryMsg::GetListingsBySeller { seller, start_after, limit, include_expired } => to_json_binary(&query_listings_by_seller(deps, env, seller, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::GetBidsByBidder { bidder, start_after, limit, include_expired } => to_json_binary(&query_bids_by_bidder(deps, env, bidder, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::GetSalesByCollection { collection, start_before, limit } => to_json_binary(&query_sales_by_collection(deps, collection, start_before, limit)?),
+        QueryMsg::GetSalesByToken { collection, token_id, start_before, limit } => to_json_binary(&query_sales_by_token(deps, collection, token_id, start_before, limit)?),
+        QueryMsg::GetLastSale { collection, token_id } => to_json_binary(&query_sales_by_token(deps, collection, token_id, None, Some(1))?.sales.pop()),
+        QueryMsg::GetCollectionStats { collection } => to_json_binary(&query_collection_stats(deps, env, collection)?),
+        QueryMsg::GetTrade { trade_id } => to_json_binary(&TRADES.load(deps.storage, trade_id)?),
+        QueryMsg::GetTrades { party, start_after, limit, include_expired } => to_json_binary(&query_trades(deps, env, party, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::GetTokenOffers { collection, token_id, start_after, limit, include_expired } => to_json_binary(&query_token_offers(deps, env, collection, token_id, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::GetOffersByBidder { bidder, start_after, limit, include_expired } => to_json_binary(&query_offers_by_bidder(deps, env, bidder, start_after, limit, include_expired.unwrap_or(false))?),
     }
 }
 
 fn query_listing(deps: Deps, collection: String, token_id: String) -> StdResult<ListingResponse> {
     let id = format!("{}:{}", collection, token_id);
-    let listing = LISTINGS.load(deps.storage, &id)?;
+    let listing = listings().load(deps.storage, (&collection, &token_id))?;
 
     let listing_response = ListingResponse {
         id: id.clone(),
@@ -495,50 +2538,579 @@ fn query_listing(deps: Deps, collection: String, token_id: String) -> StdResult<
         owner: listing.owner.clone(),
         price: listing.price.unwrap_or(0),
         bids: listing.bids.clone().unwrap_or_else(Vec::new),
+        kind: listing.kind.clone(),
+        expires: listing.expires,
+        reserved_for: listing.reserved_for.clone(),
+        custody: listing.custody.clone(),
+        listed_at: listing.listed_at,
     };
 
     Ok(listing_response)
 }
 
-fn query_listings(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<ListingsResponse> {
+fn query_listings(
+    deps: Deps,
+    env: Env,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+    include_expired: bool,
+    reserved_for: Option<String>,
+) -> StdResult<ListingsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id)| Bound::exclusive((collection.as_str(), token_id.as_str())));
+    let reserved_for = reserved_for.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+
+    let listings: Vec<ListingResponse> = listings()
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .filter(|item| include_expired || item.as_ref().map_or(true, |(_, listing)| !listing.is_expired(&env.block)))
+        // Public listings by default, or only the listings reserved for the requested buyer
+        .filter(|item| item.as_ref().map_or(true, |(_, listing)| listing.reserved_for == reserved_for))
+        .take(limit)
+        .map(|item| item.map(|(_, listing)| listing_response(listing, &env.block, include_expired)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ListingsResponse { listings })
+}
+
+fn listing_response(listing: Listing, block: &BlockInfo, include_expired: bool) -> ListingResponse {
+    ListingResponse {
+        id: format!("{}:{}", listing.collection, listing.token_id),
+        uri: listing.token_uri,
+        owner: listing.owner,
+        price: listing.price.unwrap_or(0),
+        bids: filter_bids(listing.bids.unwrap_or_else(Vec::new), block, include_expired),
+        kind: listing.kind,
+        expires: listing.expires,
+        reserved_for: listing.reserved_for,
+        custody: listing.custody,
+        listed_at: listing.listed_at,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn query_search_listings(
+    deps: Deps,
+    env: Env,
+    filter: ListingFilter,
+    sort_by: Option<ListingSort>,
+    descending: bool,
+    start_after: Option<ListingCursor>,
+    limit: Option<u32>,
+) -> StdResult<ListingsPageResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+    let order = if descending { cosmwasm_std::Order::Descending } else { cosmwasm_std::Order::Ascending };
+    let include_expired = filter.include_expired.unwrap_or(false);
+    let seller = filter.seller.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let reserved_for = filter.reserved_for.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+
+    let sort_value = |listing: &Listing| match sort_by {
+        Some(ListingSort::Price) => listing.index_price(),
+        Some(ListingSort::ListedAt) => listing.index_listed_at() as u128,
+        None => 0,
+    };
+    let keep = |listing: &Listing| {
+        filter.collection.as_ref().map_or(true, |collection| &listing.collection == collection)
+            && seller.as_ref().map_or(true, |seller| &listing.owner == seller)
+            && filter.min_price.map_or(true, |min| listing.index_price() >= min)
+            && filter.max_price.map_or(true, |max| listing.index_price() <= max)
+            && filter.has_bids.map_or(true, |has_bids| listing.bidders().is_empty() != has_bids)
+            && listing.reserved_for == reserved_for
+            && (include_expired || !listing.is_expired(&env.block))
+    };
+
+    // A collection filter ranges over that collection's part of the map or index only
+    let listing_map = listings();
+    let page = match sort_by {
+        // Pages continue after the cursor in whichever direction the results are sorted
+        Some(ListingSort::Price) => {
+            let bound = start_after.as_ref().map(|cursor| {
+                Bound::exclusive((cursor.sort_value, (cursor.collection.as_str(), cursor.token_id.as_str())))
+            });
+            let (min, max) = if descending { (None, bound) } else { (bound, None) };
+            match &filter.collection {
+                Some(collection) => collect_listings(
+                    listing_map.idx.collection_price.sub_prefix(collection.clone()).range(deps.storage, min, max, order),
+                    keep,
+                    limit,
+                )?,
+                None => collect_listings(listing_map.idx.price.range(deps.storage, min, max, order), keep, limit)?,
+            }
+        },
+        Some(ListingSort::ListedAt) => {
+            let bound = start_after.as_ref().map(|cursor| {
+                Bound::exclusive((cursor.sort_value as u64, (cursor.collection.as_str(), cursor.token_id.as_str())))
+            });
+            let (min, max) = if descending { (None, bound) } else { (bound, None) };
+            match &filter.collection {
+                Some(collection) => collect_listings(
+                    listing_map.idx.collection_listed_at.sub_prefix(collection.clone()).range(deps.storage, min, max, order),
+                    keep,
+                    limit,
+                )?,
+                None => collect_listings(listing_map.idx.listed_at.range(deps.storage, min, max, order), keep, limit)?,
+            }
+        },
+        None => match &filter.collection {
+            Some(collection) => {
+                let bound = start_after.as_ref().map(|cursor| Bound::exclusive(cursor.token_id.as_str()));
+                let (min, max) = if descending { (None, bound) } else { (bound, None) };
+                collect_listings(listing_map.prefix(collection).range(deps.storage, min, max, order), keep, limit)?
+            },
+            None => {
+                let bound = start_after.as_ref().map(|cursor| Bound::exclusive((cursor.collection.as_str(), cursor.token_id.as_str())));
+                let (min, max) = if descending { (None, bound) } else { (bound, None) };
+                collect_listings(listing_map.range(deps.storage, min, max, order), keep, limit)?
+            },
+        },
+    };
+
+    let next = match page.len() == limit {
+        true => page.last().map(|listing| ListingCursor {
+            sort_value: sort_value(listing),
+            collection: listing.collection.clone(),
+            token_id: listing.token_id.clone(),
+        }),
+        false => None,
+    };
 
-    let listings: Vec<ListingResponse> = LISTINGS
+    Ok(ListingsPageResponse {
+        listings: page.into_iter().map(|listing| listing_response(listing, &env.block, include_expired)).collect(),
+        next,
+    })
+}
+
+fn collect_listings<K>(
+    items: impl Iterator<Item = StdResult<(K, Listing)>>,
+    keep: impl Fn(&Listing) -> bool,
+    limit: usize,
+) -> StdResult<Vec<Listing>> {
+    items
+        .map(|item| item.map(|(_, listing)| listing))
+        .filter(|item| item.as_ref().map_or(true, &keep))
+        .take(limit)
+        .collect()
+}
+
+fn query_listings_by_seller(
+    deps: Deps,
+    env: Env,
+    seller: String,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<ListingsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let seller = deps.api.addr_validate(&seller)?;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id)| Bound::exclusive((collection.as_str(), token_id.as_str())));
+
+    let listings = listings()
+        .idx
+        .seller
+        .prefix(seller)
         .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, listing)| listing))
+        .filter(|item| include_expired || item.as_ref().map_or(true, |listing| !listing.is_expired(&env.block)))
         .take(limit)
-        .map(|item| {
-            let (key, listing) = item?;
-            let id = String::from_utf8(key.into())?;
-            Ok(ListingResponse { 
-                id, 
-                uri: listing.token_uri,
-                owner: listing.owner, 
-                price: listing.price.unwrap_or(0),
-                bids: listing.bids.unwrap_or_else(Vec::new),
-                // other fields as necessary 
-            })
-        })
+        .map(|item| item.map(|listing| listing_response(listing, &env.block, include_expired)))
         .collect::<StdResult<Vec<_>>>()?;
 
     Ok(ListingsResponse { listings })
 }
 
+fn query_bids_by_bidder(
+    deps: Deps,
+    env: Env,
+    bidder: String,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<BidderBidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id)| Bound::exclusive((collection.as_str(), token_id.as_str())));
+
+    let keys = BIDS_BY_BIDDER
+        .prefix(&bidder)
+        .keys(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut bids = vec![];
+    for (collection, token_id) in keys {
+        let listing = listings().load(deps.storage, (&collection, &token_id))?;
+        let highest_bid = match &listing.kind {
+            ListingKind::Auction(auction) => auction.highest_bid.as_ref(),
+            _ => None,
+        };
+        let bid = listing.bids.iter().flatten().chain(highest_bid).find(|bid| bid.bidder == bidder);
+        if let Some(bid) = bid.filter(|bid| include_expired || !bid.is_expired(&env.block)) {
+            bids.push(BidderBid {
+                collection: collection.clone(),
+                token_id: token_id.clone(),
+                amount: bid.amount,
+                expires: bid.expires,
+            });
+        }
+        if bids.len() == limit {
+            break;
+        }
+    }
+
+    Ok(BidderBidsResponse { bids })
+}
+
+fn filter_bids(bids: Vec<Bid>, block: &BlockInfo, include_expired: bool) -> Vec<Bid> {
+    bids.into_iter()
+        .filter(|bid| include_expired || !bid.is_expired(block))
+        .collect()
+}
+
+fn query_approved_collections(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CollectionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let collections = APPROVED_COLLECTIONS
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, collection)| collection))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CollectionsResponse { collections })
+}
+
 fn query_state_owner(deps: Deps) -> StdResult<Addr> {
-    let state = STATE.load(deps.storage)?;
-    Ok(state.owner)
+    cw_ownable::get_ownership(deps.storage)?
+        .owner
+        .ok_or_else(|| StdError::not_found("owner"))
+}
+
+fn query_roles(deps: Deps, address: String) -> StdResult<RolesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let roles = Role::all()
+        .into_iter()
+        .filter(|role| ROLES.has(deps.storage, (role.as_str(), &address)))
+        .collect();
+
+    Ok(RolesResponse { roles })
+}
+
+fn query_legacy_bids(
+    deps: Deps,
+    start_after: Option<(String, String, String)>,
+    limit: Option<u32>,
+) -> StdResult<LegacyBidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(collection, token_id, bidder)| deps.api.addr_validate(&bidder).map(|bidder| (collection, token_id, bidder)))
+        .transpose()?;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id, bidder)| Bound::exclusive((collection.as_str(), token_id.as_str(), bidder)));
+
+    let bids = LEGACY_BIDS
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|((collection, token_id, _), bid)| LegacyBid { collection, token_id, bid }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(LegacyBidsResponse { bids })
+}
+
+fn query_role_members(
+    deps: Deps,
+    role: Role,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RoleMembersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let members = ROLES
+        .prefix(role.as_str())
+        .keys(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(RoleMembersResponse { role, members })
+}
+
+fn query_dutch_price(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    token_id: String,
+    time: Option<Timestamp>,
+) -> StdResult<DutchPriceResponse> {
+    let listing = listings().load(deps.storage, (&collection, &token_id))?;
+    let time = time.unwrap_or(env.block.time);
+
+    match listing.kind {
+        ListingKind::Dutch(dutch) => Ok(DutchPriceResponse { price: dutch.price_at(time), time }),
+        _ => Err(StdError::generic_err("Listing is not a Dutch auction")),
+    }
+}
+
+fn query_collection_offers(
+    deps: Deps,
+    env: Env,
+    collection: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<CollectionOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // A collection filter walks that collection's ids only
+    let offers: Box<dyn Iterator<Item = StdResult<CollectionOffer>>> = match &collection {
+        Some(collection) => Box::new(COLLECTION_OFFERS_BY_COLLECTION
+            .prefix(collection)
+            .keys(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+            .map(|id| id.and_then(|id| COLLECTION_OFFERS.load(deps.storage, id)))),
+        None => Box::new(COLLECTION_OFFERS
+            .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+            .map(|item| item.map(|(_, offer)| offer))),
+    };
+    let offers = offers
+        .filter(|item| item.as_ref().map_or(true, |offer| include_expired || !offer.is_expired(&env.block)))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CollectionOffersResponse { offers })
+}
+
+fn query_bundles(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<BundlesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let bundles = BUNDLES
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, bundle)| bundle))
+        .filter(|item| include_expired || item.as_ref().map_or(true, |bundle| !bundle.is_expired(&env.block)))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BundlesResponse { bundles })
+}
+
+fn query_sales_by_collection(
+    deps: Deps,
+    collection: String,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SalesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // Most recent sales first
+    let sales = sales()
+        .idx
+        .collection
+        .prefix(collection)
+        .range(deps.storage, None, start_before.map(Bound::exclusive), cosmwasm_std::Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, sale)| sale))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SalesResponse { sales })
+}
+
+fn query_sales_by_token(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SalesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let sales = sales()
+        .idx
+        .token
+        .prefix((collection, token_id))
+        .range(deps.storage, None, start_before.map(Bound::exclusive), cosmwasm_std::Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, sale)| sale))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SalesResponse { sales })
+}
+
+fn query_collection_stats(deps: Deps, env: Env, collection: String) -> StdResult<CollectionStatsResponse> {
+    let totals = COLLECTION_STATS.may_load(deps.storage, &collection)?.unwrap_or_default();
+
+    // Walk back through the collection's sales until they fall outside the longest window
+    let day_ago = env.block.time.minus_seconds(DAY_SECONDS);
+    let week_ago = env.block.time.minus_seconds(7 * DAY_SECONDS);
+    let mut volume_24h = 0;
+    let mut volume_7d = 0;
+    for item in sales()
+        .idx
+        .collection
+        .prefix(collection.clone())
+        .range(deps.storage, None, None, cosmwasm_std::Order::Descending)
+    {
+        let (_, sale) = item?;
+        if sale.time < week_ago {
+            break;
+        }
+        volume_7d += sale.price;
+        if sale.time >= day_ago {
+            volume_24h += sale.price;
+        }
+    }
+
+    // The floor is the cheapest public listing that can be bought right now
+    let mut floor_price: Option<u128> = None;
+    for item in listings()
+        .idx
+        .collection
+        .prefix(collection)
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+    {
+        let (_, listing) = item?;
+        if listing.reserved_for.is_some() || listing.is_expired(&env.block) {
+            continue;
+        }
+        let price = match &listing.kind {
+            ListingKind::FixedPrice => listing.price,
+            ListingKind::Dutch(dutch) if env.block.time >= dutch.start_time => Some(dutch.price_at(env.block.time)),
+            _ => None,
+        };
+        if let Some(price) = price {
+            floor_price = Some(floor_price.map_or(price, |floor| floor.min(price)));
+        }
+    }
+
+    Ok(CollectionStatsResponse {
+        floor_price,
+        volume_24h,
+        volume_7d,
+        volume_all_time: totals.volume,
+        sales_count: totals.sales,
+    })
+}
+
+fn query_trades(
+    deps: Deps,
+    env: Env,
+    party: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<TradesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let party = party.map(|party| deps.api.addr_validate(&party)).transpose()?;
+    let start = start_after.map(Bound::exclusive);
+
+    let trades = TRADES
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, trade)| trade))
+        .filter(|item| item.as_ref().map_or(true, |trade| {
+            party.as_ref().map_or(true, |party| &trade.maker == party || &trade.counterparty == party)
+                && (include_expired || !trade.is_expired(&env.block))
+        }))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TradesResponse { trades })
+}
+
+fn query_token_offers(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<TokenOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|bidder| deps.api.addr_validate(&bidder)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let offers = token_offers()
+        .prefix((collection.as_str(), token_id.as_str()))
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, offer)| offer))
+        .filter(|item| include_expired || item.as_ref().map_or(true, |offer| !offer.is_expired(&env.block)))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokenOffersResponse { offers })
+}
+
+fn query_offers_by_bidder(
+    deps: Deps,
+    env: Env,
+    bidder: String,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<TokenOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id)| Bound::exclusive((collection.as_str(), token_id.as_str(), &bidder)));
+
+    let offers = token_offers()
+        .idx
+        .bidder
+        .prefix(bidder.clone())
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, offer)| offer))
+        .filter(|item| include_expired || item.as_ref().map_or(true, |offer| !offer.is_expired(&env.block)))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokenOffersResponse { offers })
+}
+
+fn query_fees(deps: Deps) -> StdResult<FeesResponse> {
+    let fee_config = FEE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let totals = FEE_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(FeesResponse {
+        fee_bps: fee_config.fee_bps,
+        fee_collector: fee_config.fee_collector,
+        collected: totals.collected,
+        accrued: totals.accrued,
+        withdrawn: totals.withdrawn,
+    })
 }
 
-fn query_bid(deps: Deps, collection: String, token_id: String) -> StdResult<BidResponse> {
+fn query_bid(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<BidResponse> {
     // Create a unique key for the listing using the collection name and token ID
-    let listing_key = format!("{}:{}", &collection, &token_id);
+    let listing_key = (collection.as_str(), token_id.as_str());
 
     // Load the listing
-    let listing = LISTINGS.load(deps.storage, &listing_key)
+    let listing = listings().load(deps.storage, listing_key)
         .map_err(|_| StdError::not_found("Listing"))?;
 
     // Clone the bids
-    let bids = listing.bids.unwrap_or_else(Vec::new);
+    let bids = filter_bids(listing.bids.unwrap_or_else(Vec::new), &env.block, include_expired);
 
     Ok(BidResponse { 
         token_id,
@@ -548,14 +3120,80 @@ fn query_bid(deps: Deps, collection: String, token_id: String) -> StdResult<BidR
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
+    mut deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    let response = upgrades::migrate(deps.branch())?;
+    let response = apply_migrate_config(deps.branch(), &env, response, msg)?;
+    validate_state(deps.as_ref())?;
+
+    Ok(response)
+}
+
+/// Applies the config changes carried by a MigrateMsg. Unset fields are left alone.
+fn apply_migrate_config(
     deps: DepsMut,
-    _env: Env,
-    _msg: MigrateMsg,
+    env: &Env,
+    mut response: Response,
+    msg: MigrateMsg,
 ) -> Result<Response, ContractError> {
-    // Update the contract's version
-    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if msg.fee_bps.is_some() || msg.fee_collector.is_some() {
+        let mut fee_config = FEE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+        if let Some(fee_bps) = msg.fee_bps {
+            fee_config.fee_bps = fee_bps;
+            response = response.add_attribute("fee_bps", fee_bps.to_string());
+        }
+        if let Some(fee_collector) = msg.fee_collector {
+            let fee_collector = deps.api.addr_validate(&fee_collector)?;
+            response = response.add_attribute("fee_collector", fee_collector.clone());
+            fee_config.fee_collector = Some(fee_collector);
+        }
+        FEE_CONFIG.save(deps.storage, &fee_config)?;
+    }
 
-    Ok(Response::new()
-        .add_attribute("method", "migrate")
-        .add_attribute("version", CONTRACT_VERSION))
-}
\ No newline at end of file
+    if let Some(denom) = msg.denom {
+        let current = payment_denom(deps.storage)?;
+        // Bids, offers and accrued fees are held in the current denom and refunded in it
+        let escrowed = deps.querier.query_balance(&env.contract.address, &current)?;
+        if denom != current && !escrowed.amount.is_zero() {
+            return Err(ContractError::EscrowOutstanding { denom: current });
+        }
+        PAYMENT_DENOM.save(deps.storage, &denom)?;
+        response = response.add_attribute("denom", denom);
+    }
+
+    if let Some(owner) = msg.owner {
+        // The chain admin running the migration can recover ownership, dropping any pending transfer
+        let ownership = cw_ownable::initialize_owner(deps.storage, deps.api, Some(owner.as_str()))?;
+        response = response.add_attributes(ownership.into_attributes());
+    }
+
+    Ok(response)
+}
+
+/// Sanity checks run after every migration, so a bad upgrade reverts instead of leaving broken state
+fn validate_state(deps: Deps) -> Result<(), ContractError> {
+    if cw_ownable::get_ownership(deps.storage)?.owner.is_none() {
+        return Err(ContractError::CustomError { val: "Marketplace has no owner".to_string() });
+    }
+
+    let fee_config = FEE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if fee_config.fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    if payment_denom(deps.storage)?.is_empty() {
+        return Err(ContractError::CustomError { val: "Payment denom is empty".to_string() });
+    }
+
+    for item in APPROVED_COLLECTIONS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+        validate_collection(deps, &item?.1)?;
+    }
+
+    if LEGACY_LISTINGS.range_raw(deps.storage, None, None, cosmwasm_std::Order::Ascending).next().is_some() {
+        return Err(ContractError::CustomError { val: "Legacy listings were not migrated".to_string() });
+    }
+
+    Ok(())
+}
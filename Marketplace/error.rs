@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_ownable::OwnershipError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +7,12 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error(transparent)]
+    Ownership(#[from] OwnershipError),
+
+    #[error(transparent)]
+    Version(#[from] cw2::VersionError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -24,6 +31,90 @@ pub enum ContractError {
     #[error("Token is not for sale")]
     TokenNotForSale {},  // <-- Add this line
 
+    #[error("Royalty percentage must be between 0 and 100")]
+    InvalidRoyaltyPercentage {},
+
+    #[error("Fee must be between 0 and 10000 basis points")]
+    InvalidFee {},
+
+    #[error("Insufficient accrued fees")]
+    InsufficientFees {},
+
+    #[error("Invalid auction")]
+    InvalidAuction {},
+
+    #[error("Auction is not accepting bids")]
+    AuctionNotActive {},
+
+    #[error("Auction has not ended")]
+    AuctionNotEnded {},
+
+    #[error("Bid too low, minimum bid is {min_bid}")]
+    BidTooLow { min_bid: u128 },
+
+    #[error("Operation not supported for auction listings")]
+    AuctionListing {},
+
+    #[error("Listing has expired")]
+    ListingExpired {},
+
+    #[error("Listing has not expired")]
+    ListingNotExpired {},
+
+    #[error("Bid has expired")]
+    BidExpired {},
+
+    #[error("Offer not found")]
+    OfferNotFound {},
+
+    #[error("Offer has expired")]
+    OfferExpired {},
+
+    #[error("Token does not match the offer")]
+    OfferMismatch {},
+
+    #[error("Token is already listed")]
+    AlreadyListed {},
+
+    #[error("Listing is reserved for another buyer")]
+    ReservedListing {},
+
+    #[error("Marketplace is not approved to transfer this token")]
+    NotApproved {},
+
+    #[error("Bundles need between 2 and {max} distinct tokens")]
+    InvalidBundle { max: usize },
+
+    #[error("Invalid trade")]
+    InvalidTrade {},
+
+    #[error("Marketplace {scope} are paused")]
+    Paused { scope: String },
+
+    #[error("Collection is paused")]
+    CollectionPaused {},
+
+    #[error("Collection is still referenced by listings, offers, bundles or trades, close them with ClearCollection")]
+    CollectionInUse {},
+
+    #[error("Contract {contract_addr} is already approved as collection {name}")]
+    DuplicateCollectionAddress { contract_addr: String, name: String },
+
+    #[error("Invalid version: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("Cannot migrate from {from} down to {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Migration from version {from} is not supported")]
+    UnsupportedMigration { from: String },
+
+    #[error("Migration step {step} is newer than contract version {version}")]
+    MigrationAheadOfVersion { step: String, version: String },
+
+    #[error("Funds are still escrowed in {denom}")]
+    EscrowOutstanding { denom: String },
+
     #[error("CW721 contract error: {0}")]
     CW721ContractError(String),
 
@@ -1,211 +1,1964 @@
-// #[cfg(test)]
-// mod tests {
-    
-
-//     use cw721_base::MintMsg;
-//     use cw721_base::entry::{execute as nftExecute, instantiate as nftInstantiate, query as nftQuery};
-//     use crate::helpers::CwTemplateContract;
-//     use crate::msg::{InstantiateMsg, QueryMsg, ListingResponse};
-//     use crate::state::{Collection, Listing, Bid};
-//     use cosmwasm_std::{Addr, Coin, Empty, Uint128, to_binary};
-//     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
-
-//     pub fn contract_template() -> Box<dyn Contract<Empty>> {
-//         let contract = ContractWrapper::new(
-//             crate::contract::execute,
-//             crate::contract::instantiate,
-//             crate::contract::query,
-//         );
-//         Box::new(contract)
-//     }
-
-//     pub fn nft_template() -> Box<dyn Contract<Empty>> {
-//         let contract = ContractWrapper::new(
-//             nftExecute,
-//             nftInstantiate,
-//             nftQuery,
-//         );
-//         Box::new(contract)
-//     }
-
-//     const USER: &str = "user";
-//     const ADMIN: &str = "admin";
-//     const NATIVE_DENOM: &str = "denom";
-
-//     fn mock_app() -> App {
-//         AppBuilder::new().build(|router, _, storage| {
-//             router
-//                 .bank
-//                 .init_balance(
-//                     storage,
-//                     &Addr::unchecked(USER),
-//                     vec![Coin {
-//                         denom: NATIVE_DENOM.to_string(),
-//                         amount: Uint128::new(1),
-//                     }],
-//                 )
-//                 .unwrap();
-//         })
-//     }
-
-//     pub fn proper_instantiate() -> (App, CwTemplateContract, CwTemplateContract) {
-//         let mut app = mock_app();
-//         let cw_template_id = app.store_code(contract_template());
-//         let nft_template_id = app.store_code(nft_template());
-    
-//         let msg = InstantiateMsg { 
-//             count: 1i32, 
-//             approved_collections: vec![],  // Initializing it as an empty vector for now
-//         };
-//         let cw_template_contract_addr = app
-//             .instantiate_contract(
-//                 cw_template_id,
-//                 Addr::unchecked(ADMIN),
-//                 &msg,
-//                 &[],
-//                 "test",
-//                 None,
-//             )
-//             .unwrap();
-
-//         let msg = cw721_base::InstantiateMsg {
-//             name: "collection".to_string(),
-//             symbol: "c".to_string(),
-//             minter: ADMIN.to_string(),
-//         };
-//         let nft_template_contract_addr = app
-//             .instantiate_contract(
-//                 nft_template_id,
-//                 Addr::unchecked(ADMIN),
-//                 &msg,
-//                 &[],
-//                 "nft",
-//                 None,
-//             )
-//             .unwrap();
-    
-//         let cw_template_contract = CwTemplateContract(cw_template_contract_addr);
-//         let nft_template_contract = CwTemplateContract(nft_template_contract_addr);
-    
-//         (app, cw_template_contract, nft_template_contract)
-//     }
-
-//     #[test]
-//     pub fn test(){
-//         use crate::msg::ExecuteMsg;
-
-//         let (mut app, cw_template_contract, nft_template_contract) = proper_instantiate();
-
-//         let msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::Mint(MintMsg{
-//             token_id: "1".to_string(),
-//             owner: ADMIN.to_string(),
-//             token_uri: None,
-//             extension: Empty{},
-//         });
-//         app.execute_contract(Addr::unchecked(ADMIN), nft_template_contract.addr(), &msg, &[]).unwrap();
-
-//         let msg = ExecuteMsg::AddApprovedCollection {
-//             collection: Collection{ contract_addr: nft_template_contract.addr(), name: nft_template_contract.addr().to_string() },
-//         };
-//         let cosmos_msg = cw_template_contract.call(msg).unwrap();
-//         app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
-
-//         let msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::SendNft { 
-//             contract: cw_template_contract.addr().to_string(), 
-//             token_id: "1".to_string(), 
-//             msg: to_binary(&Listing {
-//                 collection: nft_template_contract.addr().to_string(),
-//                 token_id: "1".to_string(),
-//                 owner: Addr::unchecked(ADMIN),
-//                 price: None,
-//                 status: crate::state::ListingStatus::Listed,
-//             }).unwrap()
-//         };
-//         app.execute_contract(Addr::unchecked(ADMIN), nft_template_contract.addr(), &msg, &[]).unwrap();
-
-//         let result: ListingResponse = app.wrap().query_wasm_smart(cw_template_contract.addr(), &QueryMsg::GetListingDetails { 
-//             collection: nft_template_contract.addr().to_string(), 
-//             token_id: "1".to_string() }).unwrap();
-//         println!("{result:?}");
-
-//         let msg = ExecuteMsg::DelistToken { collection_name: nft_template_contract.addr().to_string(), token_id: "1".to_string() };
-//         let cosmos_msg = cw_template_contract.call(msg).unwrap();
-//         app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
-
-//         let result  = app.wrap().query_wasm_smart::<ListingResponse>(cw_template_contract.addr(), &QueryMsg::GetListingDetails { 
-//             collection: nft_template_contract.addr().to_string(), 
-//             token_id: "1".to_string() }).unwrap_err();
-        
-//     }
-
-//     #[test]
-// pub fn edit_listing_test(){
-//     use crate::msg::ExecuteMsg;
-
-//     let (mut app, cw_template_contract, nft_template_contract) = proper_instantiate();
-
-//     let msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::Mint(MintMsg{
-//         token_id: "1".to_string(),
-//         owner: ADMIN.to_string(),
-//         token_uri: None,
-//         extension: Empty{},
-//     });
-//     app.execute_contract(Addr::unchecked(ADMIN), nft_template_contract.addr(), &msg, &[]).unwrap();
-
-//     let msg = ExecuteMsg::AddApprovedCollection {
-//         collection: Collection{ contract_addr: nft_template_contract.addr(), name: nft_template_contract.addr().to_string() },
-//     };
-//     let cosmos_msg = cw_template_contract.call(msg).unwrap();
-//     app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
-
-//     // List an NFT token for 500 uLuna
-//     let msg: cw721_base::ExecuteMsg<Empty, Empty> = cw721_base::ExecuteMsg::SendNft { 
-//         contract: cw_template_contract.addr().to_string(), 
-//         token_id: "1".to_string(), 
-//         msg: to_binary(&Listing {
-//             collection: nft_template_contract.addr().to_string(),
-//             token_id: "1".to_string(),
-//             owner: Addr::unchecked(ADMIN),
-//             price: Some(vec![Coin {
-//                 denom: "uluna".to_string(),
-//                 amount: Uint128::from(500u128),
-//             }]),
-//             status: crate::state::ListingStatus::Listed,
-//         }).unwrap()
-//     };
-//     app.execute_contract(Addr::unchecked(ADMIN), nft_template_contract.addr(), &msg, &[]).unwrap();
-
-//     // Query the token's current listing
-//     let initial_listing: ListingResponse = app.wrap().query_wasm_smart(cw_template_contract.addr(), &QueryMsg::GetListingDetails { 
-//         collection: nft_template_contract.addr().to_string(), 
-//         token_id: "1".to_string() }).unwrap();
-
-//     // Assert the initial listing price is 500 uLuna
-//     assert_eq!(initial_listing.price, Some(vec![Coin {
-//         denom: "uluna".to_string(),
-//         amount: Uint128::from(500u128),
-//     }]));
-
-//     // Edit the token's listing to 600 uLuna
-//     let msg = ExecuteMsg::EditListing { 
-//         collection: nft_template_contract.addr().to_string(), 
-//         token_id: "1".to_string(), 
-//         new_amount: 600 
-//     };
-//     let cosmos_msg = cw_template_contract.call(msg).unwrap();
-//     app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
-
-//     // Query the token's edited listing
-//     let edited_listing: ListingResponse = app.wrap().query_wasm_smart(cw_template_contract.addr(), &QueryMsg::GetListingDetails { 
-//         collection: nft_template_contract.addr().to_string(), 
-//         token_id: "1".to_string() }).unwrap();
-
-//     // Assert the edited listing price is 600 uLuna
-//     assert_eq!(edited_listing.price, Some(vec![Coin {
-//         denom: "uluna".to_string(),
-//         amount: Uint128::from(600u128),
-//     }]));
-//     }
-
-    
-// }
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        coins, to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+    };
+    use cw_multi_test::{App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor};
+    use cw721::{Cw721ReceiveMsg, Expiration};
+    use cw_storage_plus::Item;
+    use mothermint::state::{KeyDetails, KeyType, PriceUpdateType, Rarity, TokenType};
+    use serde::{Deserialize, Serialize};
+
+    use crate::contract;
+    use crate::msg::{
+        BatchBuyItem, BidderBidsResponse, CheckRoyaltiesResponse, CollectionQueryMsg, CollectionStatsResponse, Cw2981QueryMsg,
+        DutchPriceResponse, ExecuteMsg, FeesResponse, InstantiateMsg, ListingCursor, ListingFilter, ListingResponse, ListingSort, ListingsPageResponse,
+        ListingsResponse, MigrateMsg, QueryMsg,
+        ReceiveNftMsg, RoleMembersResponse, RolesResponse, RoyaltiesInfoResponse, SalesResponse, TokenOffersResponse, TokenTraitsResponse,
+    };
+    use crate::state::{
+        Auction, Bundle, Collection, CollectionOffer, DutchAuction, ListingCustody, ListingKind, PauseScope, PauseState,
+        Role, Sale, SaleKind, TokenRef, TraitFilter, DEFAULT_DENOM,
+    };
+    use crate::ContractError;
+
+    const ADMIN: &str = "admin";
+    const SELLER: &str = "seller";
+    const BUYER: &str = "buyer";
+    const BIDDER: &str = "bidder";
+    const BIDDER2: &str = "bidder2";
+    const ARTIST: &str = "artist";
+    const COLLECTOR: &str = "collector";
+    const FEE_MANAGER: &str = "fee_manager";
+    const MODERATOR: &str = "moderator";
+    const GUARDIAN: &str = "guardian";
+    const CURATOR: &str = "curator";
+    const COLLECTION: &str = "glochips";
+    const DENOM: &str = DEFAULT_DENOM;
+    const STARTING_BALANCE: u128 = 10_000;
+
+    /// cw721-base collection that also answers the cw2981 and TokenTraits queries the Marketplace sends.
+    /// Every token pays `royalty_percentage` of its sale price to ARTIST.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct MockCollectionInstantiateMsg {
+        royalty_percentage: u64,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[serde(untagged)]
+    enum MockCollectionQueryMsg {
+        Marketplace(CollectionQueryMsg),
+        Cw721(cw721_base::QueryMsg<Empty>),
+    }
+
+    const ROYALTY_PERCENTAGE: Item<u64> = Item::new("royalty_percentage");
+
+    fn mock_collection_instantiate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: MockCollectionInstantiateMsg,
+    ) -> StdResult<Response> {
+        ROYALTY_PERCENTAGE.save(deps.storage, &msg.royalty_percentage)?;
+        cw721_base::entry::instantiate(
+            deps,
+            env,
+            info,
+            cw721_base::InstantiateMsg {
+                name: "GloChips".to_string(),
+                symbol: "GLO".to_string(),
+                minter: ADMIN.to_string(),
+            },
+        )
+    }
+
+    fn mock_collection_query(deps: Deps, env: Env, msg: MockCollectionQueryMsg) -> StdResult<Binary> {
+        match msg {
+            MockCollectionQueryMsg::Marketplace(CollectionQueryMsg::Extension { msg }) => match msg {
+                Cw2981QueryMsg::CheckRoyalties {} => to_json_binary(&CheckRoyaltiesResponse { royalty_payments: true }),
+                Cw2981QueryMsg::RoyaltyInfo { sale_price, .. } => {
+                    let percentage = ROYALTY_PERCENTAGE.load(deps.storage)?;
+                    to_json_binary(&RoyaltiesInfoResponse {
+                        address: if percentage == 0 { String::new() } else { ARTIST.to_string() },
+                        royalty_amount: sale_price.multiply_ratio(percentage, 100u128),
+                    })
+                },
+            },
+            MockCollectionQueryMsg::Marketplace(CollectionQueryMsg::TokenTraits { token_id }) => {
+                to_json_binary(&TokenTraitsResponse {
+                    base_id: token_id,
+                    rarity: Some("generic".to_string()),
+                    season_id: None,
+                    special: false,
+                })
+            },
+            MockCollectionQueryMsg::Cw721(msg) => cw721_base::entry::query(deps, env, msg),
+        }
+    }
+
+    fn mock_collection() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw721_base::entry::execute,
+            mock_collection_instantiate,
+            mock_collection_query,
+        );
+        Box::new(contract)
+    }
+
+    fn marketplace_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            contract::execute,
+            contract::instantiate,
+            contract::query,
+        )
+        .with_migrate(contract::migrate);
+        Box::new(contract)
+    }
+
+    fn mock_app() -> App {
+        AppBuilder::new().build(|router, _, storage| {
+            for user in [BUYER, BIDDER, BIDDER2] {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(user), coins(STARTING_BALANCE, DENOM))
+                    .unwrap();
+            }
+        })
+    }
+
+    fn instantiate_collection(app: &mut App, royalty_percentage: u64) -> Addr {
+        let code_id = app.store_code(mock_collection());
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &MockCollectionInstantiateMsg { royalty_percentage },
+            &[],
+            "collection",
+            None,
+        )
+        .unwrap()
+    }
+
+    fn approved_collection(contract_addr: &Addr, name: &str) -> Collection {
+        Collection {
+            contract_addr: contract_addr.clone(),
+            name: name.to_string(),
+            max_royalty_percentage: None,
+            fee_bps: None,
+            paused: false,
+        }
+    }
+
+    fn instantiate_marketplace(app: &mut App, approved_collections: Vec<Collection>) -> Result<Addr, ContractError> {
+        let code_id = app.store_code(marketplace_contract());
+        let msg = InstantiateMsg { count: 1i32, approved_collections, denom: None };
+        app.instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "marketplace", Some(ADMIN.to_string()))
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    /// Instantiates a collection paying `royalty_percentage` royalties and a Marketplace approving it
+    fn proper_instantiate(royalty_percentage: u64) -> (App, Addr, Addr) {
+        let mut app = mock_app();
+        let collection_addr = instantiate_collection(&mut app, royalty_percentage);
+        let marketplace_addr = instantiate_marketplace(&mut app, vec![approved_collection(&collection_addr, COLLECTION)]).unwrap();
+
+        (app, marketplace_addr, collection_addr)
+    }
+
+    fn execute(
+        app: &mut App,
+        sender: &str,
+        marketplace: &Addr,
+        msg: &ExecuteMsg,
+        funds: u128,
+    ) -> Result<AppResponse, ContractError> {
+        let funds = if funds == 0 { vec![] } else { coins(funds, DENOM) };
+        app.execute_contract(Addr::unchecked(sender), marketplace.clone(), msg, &funds)
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    fn mint(app: &mut App, collection: &Addr, owner: &str, token_id: &str) {
+        let msg: cw721_base::ExecuteMsg<Option<Empty>, Empty> = cw721_base::ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: Some(format!("ipfs://{}", token_id)),
+            extension: None,
+        };
+        app.execute_contract(Addr::unchecked(ADMIN), collection.clone(), &msg, &[]).unwrap();
+    }
+
+    fn execute_collection(app: &mut App, sender: &str, collection: &Addr, msg: cw721_base::ExecuteMsg<Option<Empty>, Empty>) {
+        app.execute_contract(Addr::unchecked(sender), collection.clone(), &msg, &[]).unwrap();
+    }
+
+    fn list_with_approval(
+        app: &mut App,
+        seller: &str,
+        marketplace: &Addr,
+        token_id: &str,
+        kind: ListingKind,
+    ) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::ListWithApproval {
+            collection: COLLECTION.to_string(),
+            token_id: token_id.to_string(),
+            price: Some(1_000),
+            kind,
+            expires: None,
+            reserved_for: None,
+        };
+        execute(app, seller, marketplace, &msg, 0)
+    }
+
+    /// Mints a token to SELLER and escrows it on the Marketplace through SendNft
+    fn list(app: &mut App, marketplace: &Addr, collection: &Addr, token_id: &str, price: Option<u128>, kind: ListingKind) {
+        list_until(app, marketplace, collection, token_id, price, kind, None);
+    }
+
+    fn list_until(
+        app: &mut App,
+        marketplace: &Addr,
+        collection: &Addr,
+        token_id: &str,
+        price: Option<u128>,
+        kind: ListingKind,
+        expires: Option<Expiration>,
+    ) {
+        let msg = ReceiveNftMsg::List { price, kind, expires, reserved_for: None };
+        mint(app, collection, SELLER, token_id);
+        send_nft(app, SELLER, marketplace, collection, token_id, &msg).unwrap();
+    }
+
+    fn send_nft(
+        app: &mut App,
+        sender: &str,
+        marketplace: &Addr,
+        collection: &Addr,
+        token_id: &str,
+        msg: &ReceiveNftMsg,
+    ) -> Result<AppResponse, String> {
+        let send: cw721_base::ExecuteMsg<Option<Empty>, Empty> = cw721_base::ExecuteMsg::SendNft {
+            contract: marketplace.to_string(),
+            token_id: token_id.to_string(),
+            msg: to_json_binary(msg).unwrap(),
+        };
+        // The Marketplace error surfaces through the collection's callback, so it is compared by message
+        app.execute_contract(Addr::unchecked(sender), collection.clone(), &send, &[])
+            .map_err(|err| err.root_cause().to_string())
+    }
+
+    /// Auction starting now and running for `duration` seconds
+    fn auction(app: &App, reserve_price: u128, min_increment: u128, duration: u64) -> ListingKind {
+        let now = app.block_info().time;
+        ListingKind::Auction(Auction {
+            start_time: now,
+            end_time: now.plus_seconds(duration),
+            reserve_price,
+            min_increment,
+            highest_bid: None,
+        })
+    }
+
+    /// Dutch auction falling from 1000 to 200 over the next 800 seconds
+    fn dutch(app: &App, step_seconds: Option<u64>) -> ListingKind {
+        let now = app.block_info().time;
+        ListingKind::Dutch(DutchAuction {
+            start_price: 1_000,
+            floor_price: 200,
+            start_time: now,
+            end_time: now.plus_seconds(800),
+            step_seconds,
+        })
+    }
+
+    fn dutch_price(app: &App, marketplace: &Addr, token_id: &str, after_seconds: Option<u64>) -> u128 {
+        let time = after_seconds.map(|seconds| app.block_info().time.plus_seconds(seconds));
+        let msg = QueryMsg::GetDutchPrice { collection: COLLECTION.to_string(), token_id: token_id.to_string(), time };
+        let res: DutchPriceResponse = app.wrap().query_wasm_smart(marketplace, &msg).unwrap();
+        res.price
+    }
+
+    fn settle(app: &mut App, marketplace: &Addr, token_id: &str) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::SettleAuction { collection: COLLECTION.to_string(), token_id: token_id.to_string() };
+        execute(app, BUYER, marketplace, &msg, 0)
+    }
+
+    fn buy_now(app: &mut App, buyer: &str, marketplace: &Addr, token_id: &str, funds: u128) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::BuyNow { collection: COLLECTION.to_string(), token_id: token_id.to_string() };
+        execute(app, buyer, marketplace, &msg, funds)
+    }
+
+    fn place_bid(
+        app: &mut App,
+        bidder: &str,
+        marketplace: &Addr,
+        token_id: &str,
+        amount: u128,
+        expires: Option<Expiration>,
+    ) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::PlaceBid { collection: COLLECTION.to_string(), token_id: token_id.to_string(), amount, expires };
+        execute(app, bidder, marketplace, &msg, amount)
+    }
+
+    fn has_attribute(res: &AppResponse, key: &str, value: &str) -> bool {
+        res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == key && attr.value == value))
+    }
+
+    fn balance(app: &App, address: impl Into<String>) -> u128 {
+        app.wrap().query_balance(address, DENOM).unwrap().amount.u128()
+    }
+
+    fn owner_of(app: &App, collection: &Addr, token_id: &str) -> String {
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(collection, &cw721::Cw721QueryMsg::OwnerOf { token_id: token_id.to_string(), include_expired: None })
+            .unwrap();
+        res.owner
+    }
+
+    fn fees(app: &App, marketplace: &Addr) -> FeesResponse {
+        app.wrap().query_wasm_smart(marketplace, &QueryMsg::GetFees {}).unwrap()
+    }
+
+    fn listing(app: &App, marketplace: &Addr, token_id: &str) -> Option<ListingResponse> {
+        app.wrap()
+            .query_wasm_smart(
+                marketplace,
+                &QueryMsg::GetListingDetails { collection: COLLECTION.to_string(), token_id: token_id.to_string() },
+            )
+            .ok()
+    }
+
+    #[test]
+    fn sale_pays_royalty_to_the_collection_artist() {
+        let (mut app, marketplace, collection) = proper_instantiate(10);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        assert_eq!(owner_of(&app, &collection, "1"), marketplace.to_string());
+
+        let err = buy_now(&mut app, BUYER, &marketplace, "1", 999).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        let res = buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert!(res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == "royalty_amount" && attr.value == "100")));
+
+        assert_eq!(owner_of(&app, &collection, "1"), BUYER);
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE - 1_000);
+        assert_eq!(balance(&app, ARTIST), 100);
+        assert_eq!(balance(&app, SELLER), 900);
+        assert_eq!(balance(&app, &marketplace), 0);
+        assert!(listing(&app, &marketplace, "1").is_none());
+    }
+
+    #[test]
+    fn royalty_cap_limits_the_royalty_paid() {
+        let (mut app, marketplace, collection) = proper_instantiate(30);
+
+        // only curators set caps, and never above 100%
+        let set_cap = |percentage| ExecuteMsg::SetRoyaltyCap {
+            collection: COLLECTION.to_string(),
+            max_royalty_percentage: Some(percentage),
+        };
+        let err = execute(&mut app, SELLER, &marketplace, &set_cap(5), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(&mut app, ADMIN, &marketplace, &set_cap(101), 0).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRoyaltyPercentage {}));
+        execute(&mut app, ADMIN, &marketplace, &set_cap(5), 0).unwrap();
+
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(balance(&app, ARTIST), 50);
+        assert_eq!(balance(&app, SELLER), 950);
+
+        // lifting the cap pays the full royalty again
+        let lift_cap = ExecuteMsg::SetRoyaltyCap { collection: COLLECTION.to_string(), max_royalty_percentage: None };
+        execute(&mut app, ADMIN, &marketplace, &lift_cap, 0).unwrap();
+        list(&mut app, &marketplace, &collection, "2", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "2", 1_000).unwrap();
+        assert_eq!(balance(&app, ARTIST), 50 + 300);
+        assert_eq!(balance(&app, SELLER), 950 + 700);
+    }
+
+    #[test]
+    fn collections_without_royalties_pay_the_seller_in_full() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+
+        assert_eq!(balance(&app, ARTIST), 0);
+        assert_eq!(balance(&app, SELLER), 1_000);
+    }
+
+    #[test]
+    fn fees_go_to_the_collector_and_collection_fees_override_the_global_fee() {
+        let (mut app, marketplace, collection) = proper_instantiate(10);
+
+        let set_fee = |fee_bps| ExecuteMsg::SetFee { fee_bps, fee_collector: Some(COLLECTOR.to_string()) };
+        let err = execute(&mut app, SELLER, &marketplace, &set_fee(250), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(&mut app, ADMIN, &marketplace, &set_fee(10_001), 0).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+        execute(&mut app, ADMIN, &marketplace, &set_fee(250), 0).unwrap();
+
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(balance(&app, COLLECTOR), 25);
+        assert_eq!(balance(&app, ARTIST), 100);
+        assert_eq!(balance(&app, SELLER), 875);
+
+        let set_collection_fee = ExecuteMsg::SetCollectionFee { collection: COLLECTION.to_string(), fee_bps: Some(1_000) };
+        execute(&mut app, ADMIN, &marketplace, &set_collection_fee, 0).unwrap();
+        list(&mut app, &marketplace, &collection, "2", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "2", 1_000).unwrap();
+        assert_eq!(balance(&app, COLLECTOR), 25 + 100);
+        assert_eq!(balance(&app, ARTIST), 100 + 100);
+        assert_eq!(balance(&app, SELLER), 875 + 800);
+
+        let totals = fees(&app, &marketplace);
+        assert_eq!(totals.collected, 125);
+        assert_eq!(totals.accrued, 0);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn royalties_are_paid_from_what_remains_after_the_fee() {
+        let (mut app, marketplace, collection) = proper_instantiate(100);
+        let set_fee = ExecuteMsg::SetFee { fee_bps: 1_000, fee_collector: Some(COLLECTOR.to_string()) };
+        execute(&mut app, ADMIN, &marketplace, &set_fee, 0).unwrap();
+
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(balance(&app, COLLECTOR), 100);
+        assert_eq!(balance(&app, ARTIST), 900);
+        assert_eq!(balance(&app, SELLER), 0);
+    }
+
+    #[test]
+    fn fees_accrue_without_a_collector_until_withdrawn() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let set_fee = ExecuteMsg::SetFee { fee_bps: 500, fee_collector: None };
+        execute(&mut app, ADMIN, &marketplace, &set_fee, 0).unwrap();
+
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(balance(&app, SELLER), 950);
+        assert_eq!(balance(&app, &marketplace), 50);
+        assert_eq!(fees(&app, &marketplace).accrued, 50);
+
+        let withdraw = |amount| ExecuteMsg::WithdrawFees { amount, recipient: Some(COLLECTOR.to_string()) };
+        let err = execute(&mut app, SELLER, &marketplace, &withdraw(None), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(&mut app, ADMIN, &marketplace, &withdraw(Some(51)), 0).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFees {}));
+
+        execute(&mut app, ADMIN, &marketplace, &withdraw(Some(20)), 0).unwrap();
+        execute(&mut app, ADMIN, &marketplace, &withdraw(None), 0).unwrap();
+        assert_eq!(balance(&app, COLLECTOR), 50);
+        assert_eq!(balance(&app, &marketplace), 0);
+
+        let totals = fees(&app, &marketplace);
+        assert_eq!((totals.collected, totals.accrued, totals.withdrawn), (50, 0, 50));
+        let err = execute(&mut app, ADMIN, &marketplace, &withdraw(None), 0).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFees {}));
+    }
+
+    #[test]
+    fn collectors_and_fee_managers_withdraw_accrued_fees() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let set_fee = ExecuteMsg::SetFee { fee_bps: 1_000, fee_collector: None };
+        execute(&mut app, ADMIN, &marketplace, &set_fee, 0).unwrap();
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(fees(&app, &marketplace).accrued, 100);
+
+        let withdraw = ExecuteMsg::WithdrawFees { amount: Some(40), recipient: None };
+        let err = execute(&mut app, FEE_MANAGER, &marketplace, &withdraw, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let grant = ExecuteMsg::GrantRole { role: Role::FeeManager, address: FEE_MANAGER.to_string() };
+        execute(&mut app, ADMIN, &marketplace, &grant, 0).unwrap();
+        execute(&mut app, FEE_MANAGER, &marketplace, &withdraw, 0).unwrap();
+        assert_eq!(balance(&app, FEE_MANAGER), 40);
+
+        // a collector set after the fees accrued can pull the rest to itself
+        let err = execute(&mut app, COLLECTOR, &marketplace, &withdraw, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let set_collector = ExecuteMsg::SetFee { fee_bps: 1_000, fee_collector: Some(COLLECTOR.to_string()) };
+        execute(&mut app, FEE_MANAGER, &marketplace, &set_collector, 0).unwrap();
+        execute(&mut app, COLLECTOR, &marketplace, &ExecuteMsg::WithdrawFees { amount: None, recipient: None }, 0).unwrap();
+        assert_eq!(balance(&app, COLLECTOR), 60);
+
+        let totals = fees(&app, &marketplace);
+        assert_eq!((totals.collected, totals.accrued, totals.withdrawn), (100, 0, 100));
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn bids_are_refunded_when_the_listing_is_bought() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+        place_bid(&mut app, BIDDER2, &marketplace, "1", 400, None).unwrap();
+        assert_eq!(balance(&app, &marketplace), 700);
+
+        // a new bid replaces the bidder's previous one and refunds it
+        place_bid(&mut app, BIDDER, &marketplace, "1", 350, None).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 350);
+        assert_eq!(listing(&app, &marketplace, "1").unwrap().bids.len(), 2);
+
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, SELLER), 1_000);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn accepting_a_bid_refunds_the_other_bidders() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+        place_bid(&mut app, BIDDER2, &marketplace, "1", 400, None).unwrap();
+
+        let accept = ExecuteMsg::AcceptBid { collection: COLLECTION.to_string(), token_id: "1".to_string(), bidder: BIDDER2.to_string() };
+        let err = execute(&mut app, BIDDER, &marketplace, &accept, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, SELLER, &marketplace, &accept, 0).unwrap();
+
+        assert_eq!(owner_of(&app, &collection, "1"), BIDDER2);
+        assert_eq!(balance(&app, SELLER), 400);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE - 400);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn delisting_and_moderator_removal_refund_bids() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        list(&mut app, &marketplace, &collection, "2", Some(1_000), ListingKind::FixedPrice);
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+        place_bid(&mut app, BIDDER, &marketplace, "2", 200, None).unwrap();
+
+        let delist = ExecuteMsg::DelistToken { collection_name: COLLECTION.to_string(), token_id: "1".to_string() };
+        let err = execute(&mut app, BIDDER, &marketplace, &delist, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, SELLER, &marketplace, &delist, 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 200);
+
+        let remove = ExecuteMsg::RemoveListing { collection: COLLECTION.to_string(), token_id: "2".to_string() };
+        let err = execute(&mut app, SELLER, &marketplace, &remove, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, ADMIN, &marketplace, &remove, 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "2"), SELLER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn withdrawn_and_expired_bids_and_listings_are_refunded() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let now = app.block_info().time;
+        list_until(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice, Some(Expiration::AtTime(now.plus_seconds(1_000))));
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, Some(Expiration::AtTime(now.plus_seconds(100)))).unwrap();
+        place_bid(&mut app, BIDDER2, &marketplace, "1", 400, None).unwrap();
+
+        let remove_expired = ExecuteMsg::RemoveExpiredBids { collection: COLLECTION.to_string(), token_id: "1".to_string() };
+        assert!(execute(&mut app, BUYER, &marketplace, &remove_expired, 0).is_err());
+
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        execute(&mut app, BUYER, &marketplace, &remove_expired, 0).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(listing(&app, &marketplace, "1").unwrap().bids.len(), 1);
+
+        let remove_bid = ExecuteMsg::RemoveBid { collection: COLLECTION.to_string(), token_id: "1".to_string() };
+        execute(&mut app, BIDDER2, &marketplace, &remove_bid, 0).unwrap();
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        place_bid(&mut app, BIDDER2, &marketplace, "1", 450, None).unwrap();
+
+        let return_expired = ExecuteMsg::ReturnExpiredListing { collection: COLLECTION.to_string(), token_id: "1".to_string() };
+        let err = execute(&mut app, BUYER, &marketplace, &return_expired, 0).unwrap_err();
+        assert!(matches!(err, ContractError::ListingNotExpired {}));
+
+        app.update_block(|block| block.time = block.time.plus_seconds(900));
+        execute(&mut app, BUYER, &marketplace, &return_expired, 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+        assert!(listing(&app, &marketplace, "1").is_none());
+    }
+
+    #[test]
+    fn auction_bids_beat_the_high_bid_by_the_increment() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let kind = auction(&app, 500, 50, 3_600);
+        list(&mut app, &marketplace, &collection, "1", None, kind);
+
+        place_bid(&mut app, BIDDER, &marketplace, "1", 100, None).unwrap();
+        let err = place_bid(&mut app, BIDDER2, &marketplace, "1", 120, None).unwrap_err();
+        assert!(matches!(err, ContractError::BidTooLow { min_bid: 150 }));
+
+        // the outbid bidder gets their escrow back straight away
+        place_bid(&mut app, BIDDER2, &marketplace, "1", 150, None).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 150);
+
+        // auction bids stay escrowed, and the auction can't be bought outright or cancelled
+        let remove_bid = ExecuteMsg::RemoveBid { collection: COLLECTION.to_string(), token_id: "1".to_string() };
+        let err = execute(&mut app, BIDDER2, &marketplace, &remove_bid, 0).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionListing {}));
+        let err = buy_now(&mut app, BUYER, &marketplace, "1", 150).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionListing {}));
+        let delist = ExecuteMsg::DelistToken { collection_name: COLLECTION.to_string(), token_id: "1".to_string() };
+        assert!(execute(&mut app, SELLER, &marketplace, &delist, 0).is_err());
+    }
+
+    #[test]
+    fn late_bids_extend_the_auction_before_it_settles() {
+        let (mut app, marketplace, collection) = proper_instantiate(10);
+        let kind = auction(&app, 500, 1, 3_600);
+        let end_time = app.block_info().time.plus_seconds(3_600);
+        list(&mut app, &marketplace, &collection, "1", None, kind);
+
+        place_bid(&mut app, BIDDER, &marketplace, "1", 400, None).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(3_500));
+        let res = place_bid(&mut app, BIDDER2, &marketplace, "1", 600, None).unwrap();
+
+        let extended = app.block_info().time.plus_seconds(600);
+        assert!(res.events.iter().any(|event| event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "end_time" && attr.value == extended.seconds().to_string())));
+        match listing(&app, &marketplace, "1").unwrap().kind {
+            ListingKind::Auction(auction) => assert_eq!(auction.end_time, extended),
+            kind => panic!("unexpected listing kind {:?}", kind),
+        }
+
+        // the original end time no longer closes the auction
+        app.update_block(|block| block.time = end_time);
+        let err = settle(&mut app, &marketplace, "1").unwrap_err();
+        assert!(matches!(err, ContractError::AuctionNotEnded {}));
+        place_bid(&mut app, BIDDER, &marketplace, "1", 700, None).unwrap();
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+
+        app.update_block(|block| block.time = block.time.plus_seconds(600));
+        settle(&mut app, &marketplace, "1").unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BIDDER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 700);
+        assert_eq!(balance(&app, ARTIST), 70);
+        assert_eq!(balance(&app, SELLER), 630);
+        assert_eq!(balance(&app, &marketplace), 0);
+        assert!(listing(&app, &marketplace, "1").is_none());
+    }
+
+    #[test]
+    fn auctions_below_reserve_return_the_nft_and_refund_the_bid() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let kind = auction(&app, 500, 1, 3_600);
+        list(&mut app, &marketplace, &collection, "1", None, kind.clone());
+        list(&mut app, &marketplace, &collection, "2", None, kind);
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(3_600));
+        let err = place_bid(&mut app, BIDDER2, &marketplace, "1", 400, None).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionNotActive {}));
+
+        settle(&mut app, &marketplace, "1").unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, SELLER), 0);
+
+        // an auction without bids also goes back to the seller
+        settle(&mut app, &marketplace, "2").unwrap();
+        assert_eq!(owner_of(&app, &collection, "2"), SELLER);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn dutch_prices_fall_continuously_or_in_steps() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let continuous = dutch(&app, None);
+        let stepped = dutch(&app, Some(300));
+        list(&mut app, &marketplace, &collection, "1", None, continuous);
+        list(&mut app, &marketplace, &collection, "2", None, stepped);
+
+        assert_eq!(dutch_price(&app, &marketplace, "1", None), 1_000);
+        assert_eq!(dutch_price(&app, &marketplace, "1", Some(400)), 600);
+        assert_eq!(dutch_price(&app, &marketplace, "1", Some(10_000)), 200);
+
+        assert_eq!(dutch_price(&app, &marketplace, "2", Some(299)), 1_000);
+        assert_eq!(dutch_price(&app, &marketplace, "2", Some(400)), 700);
+        assert_eq!(dutch_price(&app, &marketplace, "2", Some(799)), 400);
+        assert_eq!(dutch_price(&app, &marketplace, "2", Some(800)), 200);
+
+        // without a time the query prices at the current block
+        app.update_block(|block| block.time = block.time.plus_seconds(400));
+        assert_eq!(dutch_price(&app, &marketplace, "1", None), 600);
+    }
+
+    #[test]
+    fn dutch_purchases_refund_overpayment() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let kind = dutch(&app, None);
+        list(&mut app, &marketplace, &collection, "1", None, kind);
+
+        let err = place_bid(&mut app, BIDDER, &marketplace, "1", 500, None).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionListing {}));
+
+        app.update_block(|block| block.time = block.time.plus_seconds(400));
+        let err = buy_now(&mut app, BUYER, &marketplace, "1", 599).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        // paying the start price still only costs the current price
+        let res = buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert!(res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == "price" && attr.value == "600")));
+        assert_eq!(owner_of(&app, &collection, "1"), BUYER);
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE - 600);
+        assert_eq!(balance(&app, SELLER), 600);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn approval_listings_sell_straight_from_the_seller() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        mint(&mut app, &collection, SELLER, "1");
+        mint(&mut app, &collection, SELLER, "2");
+
+        let err = list_with_approval(&mut app, SELLER, &marketplace, "1", ListingKind::FixedPrice).unwrap_err();
+        assert!(matches!(err, ContractError::NotApproved {}));
+        let approve = cw721_base::ExecuteMsg::Approve { spender: marketplace.to_string(), token_id: "1".to_string(), expires: None };
+        execute_collection(&mut app, SELLER, &collection, approve);
+
+        let err = list_with_approval(&mut app, BUYER, &marketplace, "1", ListingKind::FixedPrice).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let kind = auction(&app, 0, 1, 3_600);
+        let err = list_with_approval(&mut app, SELLER, &marketplace, "1", kind).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        list_with_approval(&mut app, SELLER, &marketplace, "1", ListingKind::FixedPrice).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+
+        // an operator approval covers every token of the seller
+        let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+        execute_collection(&mut app, SELLER, &collection, approve_all);
+        list_with_approval(&mut app, SELLER, &marketplace, "2", ListingKind::FixedPrice).unwrap();
+
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        buy_now(&mut app, BUYER, &marketplace, "2", 1_000).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BUYER);
+        assert_eq!(owner_of(&app, &collection, "2"), BUYER);
+        assert_eq!(balance(&app, SELLER), 2_000);
+    }
+
+    #[test]
+    fn stale_approval_listings_are_invalidated_and_refunded() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        mint(&mut app, &collection, SELLER, "1");
+        mint(&mut app, &collection, SELLER, "2");
+        let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+        execute_collection(&mut app, SELLER, &collection, approve_all);
+        list_with_approval(&mut app, SELLER, &marketplace, "1", ListingKind::FixedPrice).unwrap();
+        list_with_approval(&mut app, SELLER, &marketplace, "2", ListingKind::FixedPrice).unwrap();
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+
+        let invalidate = |token_id: &str| ExecuteMsg::InvalidateListing { collection: COLLECTION.to_string(), token_id: token_id.to_string() };
+        let err = execute(&mut app, BUYER, &marketplace, &invalidate("1"), 0).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        // the seller moved the token away, so buying closes the listing and refunds everyone
+        let transfer = cw721_base::ExecuteMsg::TransferNft { recipient: ARTIST.to_string(), token_id: "1".to_string() };
+        execute_collection(&mut app, SELLER, &collection, transfer);
+        let res = buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert!(res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == "listing_invalidated" && attr.value == "1")));
+        assert_eq!(owner_of(&app, &collection, "1"), ARTIST);
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert!(listing(&app, &marketplace, "1").is_none());
+
+        // revoking the operator approval lets anyone clear the listing
+        let revoke_all = cw721_base::ExecuteMsg::RevokeAll { operator: marketplace.to_string() };
+        execute_collection(&mut app, SELLER, &collection, revoke_all);
+        execute(&mut app, BUYER, &marketplace, &invalidate("2"), 0).unwrap();
+        assert!(listing(&app, &marketplace, "2").is_none());
+        assert_eq!(owner_of(&app, &collection, "2"), SELLER);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    fn bids_by_bidder(app: &App, marketplace: &Addr, bidder: &str) -> BidderBidsResponse {
+        let msg = QueryMsg::GetBidsByBidder { bidder: bidder.to_string(), start_after: None, limit: None, include_expired: None };
+        app.wrap().query_wasm_smart(marketplace, &msg).unwrap()
+    }
+
+    #[test]
+    fn listing_a_listed_token_again_never_strands_its_bids() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        mint(&mut app, &collection, SELLER, "1");
+        mint(&mut app, &collection, SELLER, "2");
+        let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+        execute_collection(&mut app, SELLER, &collection, approve_all.clone());
+        list_with_approval(&mut app, SELLER, &marketplace, "1", ListingKind::FixedPrice).unwrap();
+        list_with_approval(&mut app, SELLER, &marketplace, "2", ListingKind::FixedPrice).unwrap();
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+        place_bid(&mut app, BIDDER2, &marketplace, "2", 200, None).unwrap();
+
+        let err = list_with_approval(&mut app, SELLER, &marketplace, "1", ListingKind::FixedPrice).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyListed {}));
+        assert_eq!(listing(&app, &marketplace, "1").unwrap().bids.len(), 1);
+
+        // once the token changes hands, the new owner's listing refunds the stale one's bidder
+        let transfer = cw721_base::ExecuteMsg::TransferNft { recipient: BUYER.to_string(), token_id: "1".to_string() };
+        execute_collection(&mut app, SELLER, &collection, transfer);
+        execute_collection(&mut app, BUYER, &collection, approve_all);
+        let res = list_with_approval(&mut app, BUYER, &marketplace, "1", ListingKind::FixedPrice).unwrap();
+        assert!(has_attribute(&res, "listing_invalidated", "1"));
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert!(bids_by_bidder(&app, &marketplace, BIDDER).bids.is_empty());
+        let relisted = listing(&app, &marketplace, "1").unwrap();
+        assert_eq!(relisted.owner, BUYER);
+        assert!(relisted.bids.is_empty());
+
+        // escrowing an approval-listed token replaces its listing the same way
+        let list_msg = ReceiveNftMsg::List { price: Some(500), kind: ListingKind::FixedPrice, expires: None, reserved_for: None };
+        send_nft(&mut app, SELLER, &marketplace, &collection, "2", &list_msg).unwrap();
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        let escrowed = listing(&app, &marketplace, "2").unwrap();
+        assert_eq!(escrowed.custody, ListingCustody::Escrow);
+        assert_eq!(escrowed.price, 500);
+        assert!(escrowed.bids.is_empty());
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn batch_buys_skip_unavailable_items_and_refund_the_rest() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        for (token_id, price) in [("1", 300), ("2", 400), ("3", 500), ("4", 600)] {
+            list(&mut app, &marketplace, &collection, token_id, Some(price), ListingKind::FixedPrice);
+        }
+        let delist = ExecuteMsg::DelistToken { collection_name: COLLECTION.to_string(), token_id: "2".to_string() };
+        execute(&mut app, SELLER, &marketplace, &delist, 0).unwrap();
+
+        let item = |token_id: &str, max_price| BatchBuyItem { collection: COLLECTION.to_string(), token_id: token_id.to_string(), max_price };
+        let items = vec![item("1", 300), item("2", 400), item("3", 500), item("4", 550)];
+
+        // without skipping, one unavailable item reverts the whole batch
+        let batch = ExecuteMsg::BatchBuy { items: items.clone(), skip_unavailable: false };
+        assert!(execute(&mut app, BUYER, &marketplace, &batch, 2_000).is_err());
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE);
+        assert!(listing(&app, &marketplace, "1").is_some());
+
+        let batch = ExecuteMsg::BatchBuy { items, skip_unavailable: true };
+        let res = execute(&mut app, BUYER, &marketplace, &batch, 2_000).unwrap();
+        assert!(has_attribute(&res, "bought", "1"));
+        assert!(has_attribute(&res, "skipped", "2"));
+        assert!(has_attribute(&res, "bought", "3"));
+        assert!(has_attribute(&res, "skipped", "4"));
+        assert!(has_attribute(&res, "bought_count", "2"));
+        assert!(has_attribute(&res, "refund", "1200"));
+
+        assert_eq!(owner_of(&app, &collection, "1"), BUYER);
+        assert_eq!(owner_of(&app, &collection, "3"), BUYER);
+        assert_eq!(owner_of(&app, &collection, "4"), marketplace.to_string());
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE - 800);
+        assert_eq!(balance(&app, SELLER), 800);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn batch_buys_stop_when_the_funds_run_out() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(300), ListingKind::FixedPrice);
+        list(&mut app, &marketplace, &collection, "2", Some(400), ListingKind::FixedPrice);
+
+        let item = |token_id: &str| BatchBuyItem { collection: COLLECTION.to_string(), token_id: token_id.to_string(), max_price: 1_000 };
+        let err = execute(&mut app, BUYER, &marketplace, &ExecuteMsg::BatchBuy { items: vec![], skip_unavailable: true }, 500).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        let batch = ExecuteMsg::BatchBuy { items: vec![item("1"), item("2")], skip_unavailable: true };
+        let res = execute(&mut app, BUYER, &marketplace, &batch, 500).unwrap();
+        assert!(has_attribute(&res, "bought_count", "1"));
+        assert!(has_attribute(&res, "refund", "200"));
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE - 300);
+        assert!(listing(&app, &marketplace, "2").is_some());
+    }
+
+    #[test]
+    fn collection_addresses_are_approved_only_once() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+
+        let err = instantiate_marketplace(
+            &mut app,
+            vec![approved_collection(&collection, COLLECTION), approved_collection(&collection, "copy")],
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateCollectionAddress { name, .. } if name == COLLECTION));
+
+        let add = |contract_addr: &Addr, name: &str| ExecuteMsg::AddApprovedCollection { collection: approved_collection(contract_addr, name) };
+        let err = execute(&mut app, ADMIN, &marketplace, &add(&collection, "copy"), 0).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateCollectionAddress { name, .. } if name == COLLECTION));
+
+        let other = instantiate_collection(&mut app, 0);
+        execute(&mut app, ADMIN, &marketplace, &add(&other, "other"), 0).unwrap();
+        let update = ExecuteMsg::UpdateCollectionAddress { name: "other".to_string(), contract_addr: collection.to_string() };
+        let err = execute(&mut app, ADMIN, &marketplace, &update, 0).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateCollectionAddress { name, .. } if name == COLLECTION));
+    }
+
+    #[test]
+    fn received_nfts_follow_collection_renames_and_address_changes() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let other = instantiate_collection(&mut app, 0);
+        let list_msg = ReceiveNftMsg::List { price: Some(500), kind: ListingKind::FixedPrice, expires: None, reserved_for: None };
+        let listed_under = |app: &App, name: &str, token_id: &str| {
+            let msg = QueryMsg::GetListingDetails { collection: name.to_string(), token_id: token_id.to_string() };
+            app.wrap().query_wasm_smart::<ListingResponse>(&marketplace, &msg).is_ok()
+        };
+
+        let rename = ExecuteMsg::RenameCollection { name: COLLECTION.to_string(), new_name: "renamed".to_string() };
+        execute(&mut app, ADMIN, &marketplace, &rename, 0).unwrap();
+        mint(&mut app, &collection, SELLER, "1");
+        send_nft(&mut app, SELLER, &marketplace, &collection, "1", &list_msg).unwrap();
+        assert!(listed_under(&app, "renamed", "1"));
+        let delist = ExecuteMsg::RemoveListing { collection: "renamed".to_string(), token_id: "1".to_string() };
+        execute(&mut app, SELLER, &marketplace, &delist, 0).unwrap();
+
+        // the old contract no longer resolves once the collection moves to a new one
+        let update = ExecuteMsg::UpdateCollectionAddress { name: "renamed".to_string(), contract_addr: other.to_string() };
+        execute(&mut app, ADMIN, &marketplace, &update, 0).unwrap();
+        let err = send_nft(&mut app, SELLER, &marketplace, &collection, "1", &list_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {}.to_string());
+        mint(&mut app, &other, SELLER, "2");
+        send_nft(&mut app, SELLER, &marketplace, &other, "2", &list_msg).unwrap();
+        assert!(listed_under(&app, "renamed", "2"));
+
+        // the released address can be approved again, and removing it releases it once more
+        let add = |name: &str| ExecuteMsg::AddApprovedCollection { collection: approved_collection(&collection, name) };
+        execute(&mut app, ADMIN, &marketplace, &add(COLLECTION), 0).unwrap();
+        let remove = ExecuteMsg::RemoveApprovedCollection { name: COLLECTION.to_string() };
+        execute(&mut app, ADMIN, &marketplace, &remove, 0).unwrap();
+        execute(&mut app, ADMIN, &marketplace, &add("again"), 0).unwrap();
+        send_nft(&mut app, SELLER, &marketplace, &collection, "1", &list_msg).unwrap();
+        assert!(listed_under(&app, "again", "1"));
+    }
+
+    #[test]
+    fn cleared_collections_return_escrow_and_can_be_removed() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        for token_id in ["1", "2", "3"] {
+            list(&mut app, &marketplace, &collection, token_id, Some(1_000), ListingKind::FixedPrice);
+            place_bid(&mut app, BIDDER, &marketplace, token_id, 100, None).unwrap();
+        }
+        let offer = ExecuteMsg::CreateCollectionOffer {
+            collection: COLLECTION.to_string(),
+            filter: None,
+            price: 100,
+            quantity: 2,
+            expires: None,
+        };
+        execute(&mut app, BIDDER2, &marketplace, &offer, 200).unwrap();
+
+        let remove = ExecuteMsg::RemoveApprovedCollection { name: COLLECTION.to_string() };
+        let err = execute(&mut app, ADMIN, &marketplace, &remove, 0).unwrap_err();
+        assert!(matches!(err, ContractError::CollectionInUse {}));
+
+        let clear = ExecuteMsg::ClearCollection { name: COLLECTION.to_string(), limit: Some(2) };
+        let err = execute(&mut app, MODERATOR, &marketplace, &clear, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let grant = ExecuteMsg::GrantRole { role: Role::Moderator, address: MODERATOR.to_string() };
+        execute(&mut app, ADMIN, &marketplace, &grant, 0).unwrap();
+
+        let res = execute(&mut app, MODERATOR, &marketplace, &clear, 0).unwrap();
+        assert!(has_attribute(&res, "in_use", "true"));
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 100);
+        let res = execute(&mut app, MODERATOR, &marketplace, &clear, 0).unwrap();
+        assert!(has_attribute(&res, "in_use", "false"));
+
+        for token_id in ["1", "2", "3"] {
+            assert_eq!(owner_of(&app, &collection, token_id), SELLER);
+        }
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+
+        execute(&mut app, ADMIN, &marketplace, &remove, 0).unwrap();
+        assert!(execute(&mut app, BIDDER, &marketplace, &offer, 200).is_err());
+    }
+
+    fn pause(app: &mut App, marketplace: &Addr, scopes: Vec<PauseScope>) {
+        execute(app, ADMIN, marketplace, &ExecuteMsg::Pause { scopes }, 0).unwrap();
+    }
+
+    fn unpause(app: &mut App, marketplace: &Addr, scopes: Vec<PauseScope>) {
+        execute(app, ADMIN, marketplace, &ExecuteMsg::Unpause { scopes }, 0).unwrap();
+    }
+
+    fn assert_paused(result: Result<AppResponse, ContractError>, paused_scope: &str) {
+        assert!(matches!(result.unwrap_err(), ContractError::Paused { scope } if scope == paused_scope));
+    }
+
+    #[test]
+    fn guardians_pause_and_only_the_owner_unpauses() {
+        let (mut app, marketplace, _) = proper_instantiate(0);
+        let all = vec![PauseScope::Listings, PauseScope::Bids, PauseScope::Purchases];
+
+        let err = execute(&mut app, GUARDIAN, &marketplace, &ExecuteMsg::Pause { scopes: all.clone() }, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let grant = ExecuteMsg::GrantRole { role: Role::Guardian, address: GUARDIAN.to_string() };
+        execute(&mut app, ADMIN, &marketplace, &grant, 0).unwrap();
+        execute(&mut app, GUARDIAN, &marketplace, &ExecuteMsg::Pause { scopes: all.clone() }, 0).unwrap();
+
+        let state: PauseState = app.wrap().query_wasm_smart(&marketplace, &QueryMsg::PauseState {}).unwrap();
+        assert_eq!(state, PauseState { listings: true, bids: true, purchases: true });
+
+        let err = execute(&mut app, GUARDIAN, &marketplace, &ExecuteMsg::Unpause { scopes: all }, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        unpause(&mut app, &marketplace, vec![PauseScope::Bids]);
+
+        let state: PauseState = app.wrap().query_wasm_smart(&marketplace, &QueryMsg::PauseState {}).unwrap();
+        assert_eq!(state, PauseState { listings: true, bids: false, purchases: true });
+    }
+
+    #[test]
+    fn each_pause_scope_blocks_only_its_own_actions() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        mint(&mut app, &collection, SELLER, "2");
+        let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+        execute_collection(&mut app, SELLER, &collection, approve_all);
+
+        pause(&mut app, &marketplace, vec![PauseScope::Listings]);
+        assert_paused(list_with_approval(&mut app, SELLER, &marketplace, "2", ListingKind::FixedPrice), "listings");
+        let list = ReceiveNftMsg::List { price: Some(1_000), kind: ListingKind::FixedPrice, expires: None, reserved_for: None };
+        assert!(send_nft(&mut app, SELLER, &marketplace, &collection, "2", &list).is_err());
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+        unpause(&mut app, &marketplace, vec![PauseScope::Listings]);
+
+        pause(&mut app, &marketplace, vec![PauseScope::Bids]);
+        assert_paused(place_bid(&mut app, BIDDER2, &marketplace, "1", 400, None), "bids");
+        let offer = ExecuteMsg::MakeOffer { collection: COLLECTION.to_string(), token_id: "2".to_string(), amount: 100, expires: None };
+        assert_paused(execute(&mut app, BIDDER2, &marketplace, &offer, 100), "bids");
+        let collection_offer = ExecuteMsg::CreateCollectionOffer {
+            collection: COLLECTION.to_string(),
+            filter: None,
+            price: 100,
+            quantity: 1,
+            expires: None,
+        };
+        assert_paused(execute(&mut app, BIDDER2, &marketplace, &collection_offer, 100), "bids");
+        list_with_approval(&mut app, SELLER, &marketplace, "2", ListingKind::FixedPrice).unwrap();
+        unpause(&mut app, &marketplace, vec![PauseScope::Bids]);
+
+        pause(&mut app, &marketplace, vec![PauseScope::Purchases]);
+        assert_paused(buy_now(&mut app, BUYER, &marketplace, "1", 1_000), "purchases");
+        let item = BatchBuyItem { collection: COLLECTION.to_string(), token_id: "2".to_string(), max_price: 1_000 };
+        let batch = ExecuteMsg::BatchBuy { items: vec![item], skip_unavailable: true };
+        assert_paused(execute(&mut app, BUYER, &marketplace, &batch, 1_000), "purchases");
+        let accept = ExecuteMsg::AcceptBid { collection: COLLECTION.to_string(), token_id: "1".to_string(), bidder: BIDDER.to_string() };
+        assert_paused(execute(&mut app, SELLER, &marketplace, &accept, 0), "purchases");
+        place_bid(&mut app, BIDDER2, &marketplace, "1", 400, None).unwrap();
+        unpause(&mut app, &marketplace, vec![PauseScope::Purchases]);
+
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BUYER);
+    }
+
+    #[test]
+    fn exit_paths_stay_open_while_paused() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        list(&mut app, &marketplace, &collection, "2", Some(1_000), ListingKind::FixedPrice);
+        mint(&mut app, &collection, SELLER, "3");
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+        place_bid(&mut app, BIDDER2, &marketplace, "2", 300, None).unwrap();
+        let offer = ExecuteMsg::MakeOffer { collection: COLLECTION.to_string(), token_id: "3".to_string(), amount: 100, expires: None };
+        execute(&mut app, BIDDER, &marketplace, &offer, 100).unwrap();
+        let collection_offer = ExecuteMsg::CreateCollectionOffer {
+            collection: COLLECTION.to_string(),
+            filter: None,
+            price: 100,
+            quantity: 2,
+            expires: None,
+        };
+        execute(&mut app, BIDDER2, &marketplace, &collection_offer, 200).unwrap();
+
+        pause(&mut app, &marketplace, vec![PauseScope::Listings, PauseScope::Bids, PauseScope::Purchases]);
+
+        let remove_bid = ExecuteMsg::RemoveBid { collection: COLLECTION.to_string(), token_id: "1".to_string() };
+        execute(&mut app, BIDDER, &marketplace, &remove_bid, 0).unwrap();
+        let delist = ExecuteMsg::DelistToken { collection_name: COLLECTION.to_string(), token_id: "1".to_string() };
+        execute(&mut app, SELLER, &marketplace, &delist, 0).unwrap();
+        let remove_listing = ExecuteMsg::RemoveListing { collection: COLLECTION.to_string(), token_id: "2".to_string() };
+        execute(&mut app, ADMIN, &marketplace, &remove_listing, 0).unwrap();
+        let cancel_offer = ExecuteMsg::CancelOffer { collection: COLLECTION.to_string(), token_id: "3".to_string() };
+        execute(&mut app, BIDDER, &marketplace, &cancel_offer, 0).unwrap();
+        execute(&mut app, BIDDER2, &marketplace, &ExecuteMsg::CancelCollectionOffer { offer_id: 1 }, 0).unwrap();
+
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+        assert_eq!(owner_of(&app, &collection, "2"), SELLER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn ended_auctions_settle_while_paused() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        let kind = auction(&app, 1, 1, 3_600);
+        list(&mut app, &marketplace, &collection, "1", None, kind);
+        let kind = auction(&app, 1_000, 1, 3_600);
+        list(&mut app, &marketplace, &collection, "2", None, kind);
+        place_bid(&mut app, BIDDER, &marketplace, "1", 400, None).unwrap();
+        place_bid(&mut app, BIDDER2, &marketplace, "2", 300, None).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(3_601));
+
+        pause(&mut app, &marketplace, vec![PauseScope::Listings, PauseScope::Bids, PauseScope::Purchases]);
+        settle(&mut app, &marketplace, "1").unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BIDDER);
+        assert_eq!(balance(&app, SELLER), 400);
+
+        // a paused collection still hands back an auction that missed its reserve
+        let pause_collection = ExecuteMsg::SetCollectionPaused { name: COLLECTION.to_string(), paused: true };
+        execute(&mut app, ADMIN, &marketplace, &pause_collection, 0).unwrap();
+        settle(&mut app, &marketplace, "2").unwrap();
+        assert_eq!(owner_of(&app, &collection, "2"), SELLER);
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    fn migrate(app: &mut App, sender: &str, marketplace: &Addr, msg: MigrateMsg) -> Result<AppResponse, ContractError> {
+        let code_id = app.store_code(marketplace_contract());
+        app.migrate_contract(Addr::unchecked(sender), marketplace.clone(), &msg, code_id)
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    fn migrate_msg() -> MigrateMsg {
+        MigrateMsg { fee_bps: None, fee_collector: None, denom: None, owner: None }
+    }
+
+    #[test]
+    fn migrate_updates_config_and_recovers_ownership() {
+        let (mut app, marketplace, _) = proper_instantiate(0);
+
+        let msg = MigrateMsg {
+            fee_bps: Some(300),
+            fee_collector: Some(COLLECTOR.to_string()),
+            denom: Some("uusd".to_string()),
+            ..migrate_msg()
+        };
+        let code_id = app.store_code(marketplace_contract());
+        assert!(app.migrate_contract(Addr::unchecked(SELLER), marketplace.clone(), &msg, code_id).is_err());
+        migrate(&mut app, ADMIN, &marketplace, msg).unwrap();
+
+        let config = fees(&app, &marketplace);
+        assert_eq!(config.fee_bps, 300);
+        assert_eq!(config.fee_collector, Some(Addr::unchecked(COLLECTOR)));
+        let denom: String = app.wrap().query_wasm_smart(&marketplace, &QueryMsg::GetDenom {}).unwrap();
+        assert_eq!(denom, "uusd");
+
+        // a renounced Marketplace can't be migrated until the chain admin hands ownership back
+        let renounce = ExecuteMsg::UpdateOwnership(cw_ownable::Action::RenounceOwnership);
+        execute(&mut app, ADMIN, &marketplace, &renounce, 0).unwrap();
+        let err = migrate(&mut app, ADMIN, &marketplace, migrate_msg()).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        migrate(&mut app, ADMIN, &marketplace, MigrateMsg { owner: Some(ADMIN.to_string()), ..migrate_msg() }).unwrap();
+        let ownership: cw_ownable::Ownership<Addr> = app.wrap().query_wasm_smart(&marketplace, &QueryMsg::Ownership {}).unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked(ADMIN)));
+    }
+
+    #[test]
+    fn migrate_keeps_escrow_refundable_and_fees_valid() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        place_bid(&mut app, BIDDER, &marketplace, "1", 300, None).unwrap();
+
+        let change_denom = || MigrateMsg { denom: Some("uusd".to_string()), ..migrate_msg() };
+        let err = migrate(&mut app, ADMIN, &marketplace, change_denom()).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowOutstanding { denom } if denom == DENOM));
+        migrate(&mut app, ADMIN, &marketplace, MigrateMsg { denom: Some(DENOM.to_string()), ..migrate_msg() }).unwrap();
+
+        let err = migrate(&mut app, ADMIN, &marketplace, MigrateMsg { fee_bps: Some(10_001), ..migrate_msg() }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+        assert_eq!(fees(&app, &marketplace).fee_bps, 0);
+
+        // once the bid is withdrawn nothing is left in the old denom
+        let remove_bid = ExecuteMsg::RemoveBid { collection: COLLECTION.to_string(), token_id: "1".to_string() };
+        execute(&mut app, BIDDER, &marketplace, &remove_bid, 0).unwrap();
+        migrate(&mut app, ADMIN, &marketplace, change_denom()).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+    }
+
+    fn collection_stats(app: &App, marketplace: &Addr) -> CollectionStatsResponse {
+        let msg = QueryMsg::GetCollectionStats { collection: COLLECTION.to_string() };
+        app.wrap().query_wasm_smart(marketplace, &msg).unwrap()
+    }
+
+    #[test]
+    fn floor_price_tracks_the_cheapest_buyable_listing() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        assert_eq!(collection_stats(&app, &marketplace).floor_price, None);
+
+        list(&mut app, &marketplace, &collection, "1", Some(800), ListingKind::FixedPrice);
+        list(&mut app, &marketplace, &collection, "2", Some(700), ListingKind::FixedPrice);
+        let kind = dutch(&app, None);
+        list(&mut app, &marketplace, &collection, "3", None, kind);
+        let kind = auction(&app, 1, 1, 3_600);
+        list(&mut app, &marketplace, &collection, "4", None, kind);
+        place_bid(&mut app, BIDDER, &marketplace, "4", 10, None).unwrap();
+
+        // private listings don't set the public floor
+        mint(&mut app, &collection, SELLER, "5");
+        let private = ReceiveNftMsg::List {
+            price: Some(100),
+            kind: ListingKind::FixedPrice,
+            expires: None,
+            reserved_for: Some(BUYER.to_string()),
+        };
+        send_nft(&mut app, SELLER, &marketplace, &collection, "5", &private).unwrap();
+        assert_eq!(collection_stats(&app, &marketplace).floor_price, Some(700));
+
+        buy_now(&mut app, BUYER, &marketplace, "2", 700).unwrap();
+        assert_eq!(collection_stats(&app, &marketplace).floor_price, Some(800));
+
+        // the Dutch auction counts at its current price
+        app.update_block(|block| block.time = block.time.plus_seconds(500));
+        assert_eq!(collection_stats(&app, &marketplace).floor_price, Some(500));
+    }
+
+    type MinterExecuteMsg = mothermint::ExecuteMsg<mothermint::Extension, Empty>;
+
+    fn minter_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            mothermint::entry::execute,
+            mothermint::entry::instantiate,
+            mothermint::entry::query,
+        );
+        Box::new(contract)
+    }
+
+    fn execute_minter(app: &mut App, sender: &str, minter: &Addr, msg: MinterExecuteMsg) -> AppResponse {
+        app.execute_contract(Addr::unchecked(sender), minter.clone(), &msg, &[]).unwrap()
+    }
+
+    /// Instantiates a Minter giving away season "s1" Esoteric and Spectral HoloKeys and a Generic key of no
+    /// season, and a Marketplace approving the Minter as COLLECTION
+    fn minter_instantiate() -> (App, Addr, Addr) {
+        let mut app = mock_app();
+        let code_id = app.store_code(minter_contract());
+        let msg = mothermint::InstantiateMsg {
+            name: "GloChips".to_string(),
+            symbol: "GLO".to_string(),
+            minter: ADMIN.to_string(),
+        };
+        let minter = app.instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "minter", None).unwrap();
+
+        execute_minter(&mut app, ADMIN, &minter, MinterExecuteMsg::CreateSeason { season_id: "s1".to_string() });
+        for (key_id, rarity) in [
+            ("esoteric_key", Rarity::Esoteric),
+            ("spectral_key", Rarity::Spectral),
+            ("generic_key", Rarity::Generic),
+        ] {
+            let msg = MinterExecuteMsg::CreateKey { id: key_id.to_string(), rarity, uri: format!("ipfs://{}", key_id) };
+            execute_minter(&mut app, ADMIN, &minter, msg);
+        }
+        for (key_id, key_type) in [("esoteric_key", KeyType::EsotericKey), ("spectral_key", KeyType::SpectralKey)] {
+            let msg = MinterExecuteMsg::AddKeyToSeason { season_id: "s1".to_string(), key_id: key_id.to_string(), key_type };
+            execute_minter(&mut app, ADMIN, &minter, msg);
+        }
+        let free_keys = MinterExecuteMsg::UpdatePrices {
+            price_update_type: PriceUpdateType::HoloKeyPrices {
+                generic_price: Some(0),
+                esoteric_price: Some(0),
+                spectral_price: Some(0),
+            },
+        };
+        execute_minter(&mut app, ADMIN, &minter, free_keys);
+
+        let marketplace = instantiate_marketplace(&mut app, vec![approved_collection(&minter, COLLECTION)]).unwrap();
+        (app, marketplace, minter)
+    }
+
+    /// Mints a HoloKey to `owner`, returning its token id
+    fn mint_key(app: &mut App, minter: &Addr, owner: &str, key_id: &str) -> String {
+        let msg = MinterExecuteMsg::Mint {
+            owner: owner.to_string(),
+            extension: None,
+            token_type: TokenType::Key(KeyDetails { key_id: key_id.to_string(), season_id: "s1".to_string() }),
+        };
+        let res = execute_minter(app, owner, minter, msg);
+        res.events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "token_id")
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    fn collection_offer(app: &App, marketplace: &Addr, offer_id: u64) -> Option<CollectionOffer> {
+        app.wrap().query_wasm_smart(marketplace, &QueryMsg::GetCollectionOffer { offer_id }).ok()
+    }
+
+    #[test]
+    fn collection_offers_are_filled_by_tokens_matching_their_minter_traits() {
+        let (mut app, marketplace, minter) = minter_instantiate();
+        let esoteric = [
+            mint_key(&mut app, &minter, SELLER, "esoteric_key"),
+            mint_key(&mut app, &minter, SELLER, "esoteric_key"),
+            mint_key(&mut app, &minter, SELLER, "esoteric_key"),
+        ];
+        let spectral = mint_key(&mut app, &minter, SELLER, "spectral_key");
+        let seasonless = mint_key(&mut app, &minter, SELLER, "generic_key");
+
+        let filter = TraitFilter { base_id: None, rarity: Some("Esoteric".to_string()), season_id: Some("s1".to_string()) };
+        let offer = ExecuteMsg::CreateCollectionOffer {
+            collection: COLLECTION.to_string(),
+            filter: Some(filter),
+            price: 200,
+            quantity: 2,
+            expires: None,
+        };
+        // every token is paid for up front
+        let err = execute(&mut app, BIDDER, &marketplace, &offer, 200).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        execute(&mut app, BIDDER, &marketplace, &offer, 400).unwrap();
+        assert_eq!(balance(&app, &marketplace), 400);
+
+        // the Minter reports the wrong rarity for one key and no season for the other
+        let fill = ReceiveNftMsg::FillCollectionOffer { offer_id: 1 };
+        for token_id in [&spectral, &seasonless] {
+            let err = send_nft(&mut app, SELLER, &marketplace, &minter, token_id, &fill).unwrap_err();
+            assert_eq!(err, ContractError::OfferMismatch {}.to_string());
+            assert_eq!(owner_of(&app, &minter, token_id), SELLER);
+        }
+
+        send_nft(&mut app, SELLER, &marketplace, &minter, &esoteric[0], &fill).unwrap();
+        assert_eq!(owner_of(&app, &minter, &esoteric[0]), BIDDER);
+        assert_eq!(balance(&app, SELLER), 200);
+        assert_eq!(balance(&app, &marketplace), 200);
+        assert_eq!(collection_offer(&app, &marketplace, 1).unwrap().filled, 1);
+
+        // the last token closes the offer
+        send_nft(&mut app, SELLER, &marketplace, &minter, &esoteric[1], &fill).unwrap();
+        assert_eq!(owner_of(&app, &minter, &esoteric[1]), BIDDER);
+        assert_eq!(balance(&app, SELLER), 400);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 400);
+        assert_eq!(balance(&app, &marketplace), 0);
+        assert!(collection_offer(&app, &marketplace, 1).is_none());
+
+        let err = send_nft(&mut app, SELLER, &marketplace, &minter, &esoteric[2], &fill).unwrap_err();
+        assert_eq!(err, ContractError::OfferNotFound {}.to_string());
+    }
+
+    #[test]
+    fn unfilled_collection_offers_are_refunded_on_cancel_and_after_expiry() {
+        let (mut app, marketplace, minter) = minter_instantiate();
+        let token_id = mint_key(&mut app, &minter, SELLER, "esoteric_key");
+
+        let offer = |price, quantity, expires| ExecuteMsg::CreateCollectionOffer {
+            collection: COLLECTION.to_string(),
+            filter: None,
+            price,
+            quantity,
+            expires,
+        };
+        execute(&mut app, BIDDER, &marketplace, &offer(100, 3, None), 300).unwrap();
+        send_nft(&mut app, SELLER, &marketplace, &minter, &token_id, &ReceiveNftMsg::FillCollectionOffer { offer_id: 1 }).unwrap();
+
+        // only the bidder cancels, getting back the two unfilled tokens
+        let cancel = |offer_id| ExecuteMsg::CancelCollectionOffer { offer_id };
+        let err = execute(&mut app, SELLER, &marketplace, &cancel(1), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, BIDDER, &marketplace, &cancel(1), 0).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 100);
+        assert_eq!(balance(&app, &marketplace), 0);
+
+        // an expired offer takes no more tokens but can still be cancelled for its escrow
+        let expires = Expiration::AtTime(app.block_info().time.plus_seconds(100));
+        execute(&mut app, BIDDER2, &marketplace, &offer(100, 2, Some(expires)), 200).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(101));
+
+        let token_id = mint_key(&mut app, &minter, SELLER, "esoteric_key");
+        let fill = ReceiveNftMsg::FillCollectionOffer { offer_id: 2 };
+        let err = send_nft(&mut app, SELLER, &marketplace, &minter, &token_id, &fill).unwrap_err();
+        assert_eq!(err, ContractError::OfferExpired {}.to_string());
+        assert_eq!(owner_of(&app, &minter, &token_id), SELLER);
+
+        execute(&mut app, BIDDER2, &marketplace, &cancel(2), 0).unwrap();
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    fn token_offers(app: &App, marketplace: &Addr, token_id: &str, include_expired: bool) -> TokenOffersResponse {
+        let msg = QueryMsg::GetTokenOffers {
+            collection: COLLECTION.to_string(),
+            token_id: token_id.to_string(),
+            start_after: None,
+            limit: None,
+            include_expired: Some(include_expired),
+        };
+        app.wrap().query_wasm_smart(marketplace, &msg).unwrap()
+    }
+
+    #[test]
+    fn token_offers_are_escrowed_until_the_owner_sends_the_nft() {
+        let (mut app, marketplace, minter) = minter_instantiate();
+        let token_id = mint_key(&mut app, &minter, SELLER, "esoteric_key");
+
+        let make_offer = |amount| ExecuteMsg::MakeOffer {
+            collection: COLLECTION.to_string(),
+            token_id: token_id.clone(),
+            amount,
+            expires: None,
+        };
+        let err = execute(&mut app, SELLER, &marketplace, &make_offer(300), 0).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        let err = execute(&mut app, BIDDER, &marketplace, &make_offer(300), 200).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        execute(&mut app, BIDDER, &marketplace, &make_offer(300), 300).unwrap();
+        execute(&mut app, BIDDER2, &marketplace, &make_offer(250), 250).unwrap();
+
+        // a bidder's new offer replaces and refunds its previous one
+        execute(&mut app, BIDDER2, &marketplace, &make_offer(280), 280).unwrap();
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE - 280);
+        assert_eq!(balance(&app, &marketplace), 580);
+        assert_eq!(token_offers(&app, &marketplace, &token_id, false).offers.len(), 2);
+
+        let accept = |bidder: &str| ReceiveNftMsg::AcceptOffer { bidder: bidder.to_string() };
+        let err = send_nft(&mut app, SELLER, &marketplace, &minter, &token_id, &accept(BUYER)).unwrap_err();
+        assert_eq!(err, ContractError::OfferNotFound {}.to_string());
+
+        send_nft(&mut app, SELLER, &marketplace, &minter, &token_id, &accept(BIDDER)).unwrap();
+        assert_eq!(owner_of(&app, &minter, &token_id), BIDDER);
+        assert_eq!(balance(&app, SELLER), 300);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 300);
+
+        // the other offer stays escrowed until its bidder cancels it
+        assert_eq!(balance(&app, &marketplace), 280);
+        let cancel = ExecuteMsg::CancelOffer { collection: COLLECTION.to_string(), token_id: token_id.clone() };
+        let err = execute(&mut app, BUYER, &marketplace, &cancel, 0).unwrap_err();
+        assert!(matches!(err, ContractError::OfferNotFound {}));
+        execute(&mut app, BIDDER2, &marketplace, &cancel, 0).unwrap();
+        assert_eq!(balance(&app, BIDDER2), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn expired_token_offers_cannot_be_accepted_but_are_refunded() {
+        let (mut app, marketplace, minter) = minter_instantiate();
+        let token_id = mint_key(&mut app, &minter, SELLER, "esoteric_key");
+
+        let expires = Expiration::AtTime(app.block_info().time.plus_seconds(100));
+        let offer = ExecuteMsg::MakeOffer {
+            collection: COLLECTION.to_string(),
+            token_id: token_id.clone(),
+            amount: 300,
+            expires: Some(expires),
+        };
+        execute(&mut app, BIDDER, &marketplace, &offer, 300).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(101));
+
+        let accept = ReceiveNftMsg::AcceptOffer { bidder: BIDDER.to_string() };
+        let err = send_nft(&mut app, SELLER, &marketplace, &minter, &token_id, &accept).unwrap_err();
+        assert_eq!(err, ContractError::OfferExpired {}.to_string());
+        assert_eq!(owner_of(&app, &minter, &token_id), SELLER);
+        assert!(token_offers(&app, &marketplace, &token_id, false).offers.is_empty());
+        assert_eq!(token_offers(&app, &marketplace, &token_id, true).offers.len(), 1);
+
+        let cancel = ExecuteMsg::CancelOffer { collection: COLLECTION.to_string(), token_id };
+        execute(&mut app, BIDDER, &marketplace, &cancel, 0).unwrap();
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn received_nfts_are_listed_from_what_the_sending_collection_reports() {
+        let (mut app, marketplace, minter) = minter_instantiate();
+        let token_id = mint_key(&mut app, &minter, BUYER, "esoteric_key");
+        let held = mint_key(&mut app, &minter, SELLER, "esoteric_key");
+
+        // the owner is whoever sent the NFT and the uri is the Minter's, not derived from the token id
+        let list_msg = ReceiveNftMsg::List { price: Some(500), kind: ListingKind::FixedPrice, expires: None, reserved_for: None };
+        send_nft(&mut app, BUYER, &marketplace, &minter, &token_id, &list_msg).unwrap();
+        let listed = listing(&app, &marketplace, &token_id).unwrap();
+        assert_eq!(listed.owner, BUYER);
+        assert_eq!(listed.uri, "ipfs://esoteric_key");
+        assert_eq!(owner_of(&app, &minter, &token_id), marketplace.to_string());
+
+        // a collection the Marketplace never approved can't list a token id of the Minter
+        let spoofed = instantiate_collection(&mut app, 0);
+        mint(&mut app, &spoofed, SELLER, &held);
+        let err = send_nft(&mut app, SELLER, &marketplace, &spoofed, &held, &list_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {}.to_string());
+
+        // nor can an account deliver a receive message for a token it never sent
+        let forged = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: SELLER.to_string(),
+            token_id: held.clone(),
+            msg: to_json_binary(&list_msg).unwrap(),
+        });
+        let err = execute(&mut app, SELLER, &marketplace, &forged, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        assert!(listing(&app, &marketplace, &held).is_none());
+        assert_eq!(owner_of(&app, &minter, &held), SELLER);
+    }
+
+    fn token_refs(token_ids: &[&str]) -> Vec<TokenRef> {
+        token_ids
+            .iter()
+            .map(|token_id| TokenRef { collection: COLLECTION.to_string(), token_id: token_id.to_string() })
+            .collect()
+    }
+
+    /// Mints `token_ids` to SELLER and approves the Marketplace as its operator
+    fn mint_approved(app: &mut App, marketplace: &Addr, collection: &Addr, token_ids: &[&str]) {
+        for token_id in token_ids {
+            mint(app, collection, SELLER, token_id);
+        }
+        let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+        execute_collection(app, SELLER, collection, approve_all);
+    }
+
+    #[test]
+    fn bundles_sell_every_item_in_one_purchase() {
+        let (mut app, marketplace, collection) = proper_instantiate(10);
+        mint_approved(&mut app, &marketplace, &collection, &["1", "2"]);
+
+        let create = |token_ids: &[&str]| ExecuteMsg::CreateBundle { items: token_refs(token_ids), price: Some(1_000), expires: None };
+        for token_ids in [&["1"][..], &["1", "1"][..]] {
+            let err = execute(&mut app, SELLER, &marketplace, &create(token_ids), 0).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidBundle { .. }));
+        }
+        let err = execute(&mut app, BUYER, &marketplace, &create(&["1", "2"]), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, SELLER, &marketplace, &create(&["1", "2"]), 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), marketplace.to_string());
+        assert_eq!(owner_of(&app, &collection, "2"), marketplace.to_string());
+
+        let bid = ExecuteMsg::PlaceBundleBid { bundle_id: 1, amount: 400, expires: None };
+        execute(&mut app, BIDDER, &marketplace, &bid, 400).unwrap();
+
+        let buy = ExecuteMsg::BuyBundle { bundle_id: 1 };
+        let err = execute(&mut app, BUYER, &marketplace, &buy, 999).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        execute(&mut app, BUYER, &marketplace, &buy, 1_000).unwrap();
+
+        // royalties are paid on each item's share and the outstanding bid is refunded
+        assert_eq!(owner_of(&app, &collection, "1"), BUYER);
+        assert_eq!(owner_of(&app, &collection, "2"), BUYER);
+        assert_eq!(balance(&app, ARTIST), 100);
+        assert_eq!(balance(&app, SELLER), 900);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+        assert!(app.wrap().query_wasm_smart::<Bundle>(&marketplace, &QueryMsg::GetBundle { bundle_id: 1 }).is_err());
+    }
+
+    #[test]
+    fn bundle_bids_are_accepted_and_cancelled_bundles_return_every_item() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        mint_approved(&mut app, &marketplace, &collection, &["1", "2", "3", "4"]);
+
+        // a bundle without a price only sells to bids
+        let create = |token_ids: &[&str], price| ExecuteMsg::CreateBundle { items: token_refs(token_ids), price, expires: None };
+        execute(&mut app, SELLER, &marketplace, &create(&["1", "2"], None), 0).unwrap();
+        execute(&mut app, SELLER, &marketplace, &create(&["3", "4"], Some(1_000)), 0).unwrap();
+        let err = execute(&mut app, BUYER, &marketplace, &ExecuteMsg::BuyBundle { bundle_id: 1 }, 1_000).unwrap_err();
+        assert!(matches!(err, ContractError::TokenNotForSale {}));
+
+        let bid = |bundle_id, amount| ExecuteMsg::PlaceBundleBid { bundle_id, amount, expires: None };
+        execute(&mut app, BIDDER, &marketplace, &bid(1, 300), 300).unwrap();
+        execute(&mut app, BIDDER2, &marketplace, &bid(1, 500), 500).unwrap();
+
+        let accept = ExecuteMsg::AcceptBundleBid { bundle_id: 1, bidder: BIDDER2.to_string() };
+        let err = execute(&mut app, BUYER, &marketplace, &accept, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, SELLER, &marketplace, &accept, 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BIDDER2);
+        assert_eq!(owner_of(&app, &collection, "2"), BIDDER2);
+        assert_eq!(balance(&app, SELLER), 500);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+
+        // cancelling returns every escrowed item and refunds the bids
+        execute(&mut app, BIDDER, &marketplace, &bid(2, 200), 200).unwrap();
+        let cancel = ExecuteMsg::CancelBundle { bundle_id: 2 };
+        let err = execute(&mut app, BUYER, &marketplace, &cancel, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, SELLER, &marketplace, &cancel, 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "3"), SELLER);
+        assert_eq!(owner_of(&app, &collection, "4"), SELLER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    fn propose_trade(counterparty: &str, offered: &[&str], requested: &[&str], requested_funds: u128) -> ExecuteMsg {
+        ExecuteMsg::ProposeTrade {
+            counterparty: counterparty.to_string(),
+            offered: token_refs(offered),
+            requested: token_refs(requested),
+            requested_funds,
+            expires: None,
+        }
+    }
+
+    #[test]
+    fn accepted_trades_swap_the_tokens_and_funds_of_both_sides() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        mint(&mut app, &collection, BIDDER, "1");
+        mint(&mut app, &collection, SELLER, "2");
+        for owner in [BIDDER, SELLER] {
+            let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+            execute_collection(&mut app, owner, &collection, approve_all);
+        }
+
+        let err = execute(&mut app, BIDDER, &marketplace, &propose_trade(BIDDER, &["1"], &["2"], 0), 200).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTrade {}));
+        execute(&mut app, BIDDER, &marketplace, &propose_trade(SELLER, &["1"], &["2"], 0), 200).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), marketplace.to_string());
+        assert_eq!(balance(&app, &marketplace), 200);
+
+        let accept = ExecuteMsg::AcceptTrade { trade_id: 1 };
+        let err = execute(&mut app, BUYER, &marketplace, &accept, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, SELLER, &marketplace, &accept, 0).unwrap();
+
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+        assert_eq!(owner_of(&app, &collection, "2"), BIDDER);
+        assert_eq!(balance(&app, SELLER), 200);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE - 200);
+        assert_eq!(balance(&app, &marketplace), 0);
+    }
+
+    #[test]
+    fn either_side_cancels_a_trade_returning_the_makers_escrow() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        mint(&mut app, &collection, BIDDER, "1");
+        let approve_all = cw721_base::ExecuteMsg::ApproveAll { operator: marketplace.to_string(), expires: None };
+        execute_collection(&mut app, BIDDER, &collection, approve_all);
+
+        // the counterparty has to pay exactly the requested funds
+        execute(&mut app, BIDDER, &marketplace, &propose_trade(BUYER, &["1"], &[], 300), 100).unwrap();
+        let err = execute(&mut app, BUYER, &marketplace, &ExecuteMsg::AcceptTrade { trade_id: 1 }, 200).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        let cancel = |trade_id| ExecuteMsg::CancelTrade { trade_id };
+        let err = execute(&mut app, SELLER, &marketplace, &cancel(1), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, BUYER, &marketplace, &cancel(1), 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BIDDER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+
+        execute(&mut app, BIDDER, &marketplace, &propose_trade(BUYER, &["1"], &[], 300), 100).unwrap();
+        execute(&mut app, BIDDER, &marketplace, &cancel(2), 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), BIDDER);
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+        assert_eq!(balance(&app, &marketplace), 0);
+
+        let err = execute(&mut app, BUYER, &marketplace, &ExecuteMsg::AcceptTrade { trade_id: 2 }, 300).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        assert_eq!(balance(&app, BUYER), STARTING_BALANCE);
+    }
+
+    /// Ids of the unexpired listings GetListings returns, public ones unless `reserved_for` is set
+    fn listing_ids(app: &App, marketplace: &Addr, reserved_for: Option<&str>) -> Vec<String> {
+        let msg = QueryMsg::GetListings {
+            start_after: None,
+            limit: None,
+            include_expired: None,
+            reserved_for: reserved_for.map(str::to_string),
+        };
+        let res: ListingsResponse = app.wrap().query_wasm_smart(marketplace, &msg).unwrap();
+        res.listings.into_iter().map(|listing| listing.id).collect()
+    }
+
+    #[test]
+    fn private_listings_are_hidden_and_sold_only_to_their_buyer() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+
+        // only fixed-price listings can be reserved
+        mint(&mut app, &collection, SELLER, "2");
+        let private = |kind| ReceiveNftMsg::List { price: Some(500), kind, expires: None, reserved_for: Some(BUYER.to_string()) };
+        let kind = auction(&app, 1, 1, 3_600);
+        assert!(send_nft(&mut app, SELLER, &marketplace, &collection, "2", &private(kind)).is_err());
+        send_nft(&mut app, SELLER, &marketplace, &collection, "2", &private(ListingKind::FixedPrice)).unwrap();
+
+        assert_eq!(listing_ids(&app, &marketplace, None), vec![format!("{}:1", COLLECTION)]);
+        assert_eq!(listing_ids(&app, &marketplace, Some(BUYER)), vec![format!("{}:2", COLLECTION)]);
+        assert!(listing_ids(&app, &marketplace, Some(BIDDER)).is_empty());
+
+        let err = place_bid(&mut app, BIDDER, &marketplace, "2", 600, None).unwrap_err();
+        assert!(matches!(err, ContractError::ReservedListing {}));
+        let err = buy_now(&mut app, BIDDER, &marketplace, "2", 500).unwrap_err();
+        assert!(matches!(err, ContractError::ReservedListing {}));
+        assert_eq!(balance(&app, BIDDER), STARTING_BALANCE);
+
+        buy_now(&mut app, BUYER, &marketplace, "2", 500).unwrap();
+        assert_eq!(owner_of(&app, &collection, "2"), BUYER);
+        assert_eq!(balance(&app, SELLER), 500);
+        assert!(listing_ids(&app, &marketplace, Some(BUYER)).is_empty());
+    }
+
+    fn search(
+        app: &App,
+        marketplace: &Addr,
+        filter: ListingFilter,
+        sort_by: Option<ListingSort>,
+        descending: bool,
+        start_after: Option<ListingCursor>,
+        limit: Option<u32>,
+    ) -> (Vec<String>, Option<ListingCursor>) {
+        let msg = QueryMsg::SearchListings { filter, sort_by, descending: Some(descending), start_after, limit };
+        let res: ListingsPageResponse = app.wrap().query_wasm_smart(marketplace, &msg).unwrap();
+        (res.listings.into_iter().map(|listing| listing.id).collect(), res.next)
+    }
+
+    fn ids(token_ids: &[&str]) -> Vec<String> {
+        token_ids.iter().map(|token_id| format!("{}:{}", COLLECTION, token_id)).collect()
+    }
+
+    #[test]
+    fn listings_are_searched_sorted_and_paged() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        for (token_id, price) in [("1", 300), ("2", 100), ("3", 200)] {
+            list(&mut app, &marketplace, &collection, token_id, Some(price), ListingKind::FixedPrice);
+            app.update_block(|block| block.time = block.time.plus_seconds(10));
+        }
+        mint(&mut app, &collection, BUYER, "4");
+        let list_msg = ReceiveNftMsg::List { price: Some(150), kind: ListingKind::FixedPrice, expires: None, reserved_for: None };
+        send_nft(&mut app, BUYER, &marketplace, &collection, "4", &list_msg).unwrap();
+        place_bid(&mut app, BIDDER, &marketplace, "1", 50, None).unwrap();
+        place_bid(&mut app, BIDDER, &marketplace, "3", 60, None).unwrap();
+
+        // filters combine, results default to key order
+        let by_seller = ListingFilter { seller: Some(SELLER.to_string()), ..Default::default() };
+        let (found, _) = search(&app, &marketplace, by_seller, Some(ListingSort::Price), false, None, None);
+        assert_eq!(found, ids(&["2", "3", "1"]));
+        let price_range = ListingFilter { min_price: Some(150), max_price: Some(250), ..Default::default() };
+        let (found, _) = search(&app, &marketplace, price_range, None, false, None, None);
+        assert_eq!(found, ids(&["3", "4"]));
+        let with_bids = ListingFilter { collection: Some(COLLECTION.to_string()), has_bids: Some(true), ..Default::default() };
+        let (found, _) = search(&app, &marketplace, with_bids, None, false, None, None);
+        assert_eq!(found, ids(&["1", "3"]));
+
+        // pages continue after the cursor in the direction of the sort
+        let (found, next) = search(&app, &marketplace, ListingFilter::default(), Some(ListingSort::ListedAt), true, None, Some(2));
+        assert_eq!(found, ids(&["4", "3"]));
+        let (found, next) = search(&app, &marketplace, ListingFilter::default(), Some(ListingSort::ListedAt), true, next, Some(2));
+        assert_eq!(found, ids(&["2", "1"]));
+        let (found, next) = search(&app, &marketplace, ListingFilter::default(), Some(ListingSort::ListedAt), true, next, Some(2));
+        assert!(found.is_empty());
+        assert!(next.is_none());
+
+        let (found, next) = search(&app, &marketplace, ListingFilter::default(), Some(ListingSort::Price), false, None, Some(3));
+        assert_eq!(found, ids(&["2", "4", "3"]));
+        let (found, next) = search(&app, &marketplace, ListingFilter::default(), Some(ListingSort::Price), false, next, Some(3));
+        assert_eq!(found, ids(&["1"]));
+        assert!(next.is_none());
+
+        let by_seller = |seller: &str| QueryMsg::GetListingsBySeller {
+            seller: seller.to_string(),
+            start_after: None,
+            limit: None,
+            include_expired: None,
+        };
+        let res: ListingsResponse = app.wrap().query_wasm_smart(&marketplace, &by_seller(SELLER)).unwrap();
+        assert_eq!(res.listings.into_iter().map(|listing| listing.id).collect::<Vec<_>>(), ids(&["1", "2", "3"]));
+        let res: ListingsResponse = app.wrap().query_wasm_smart(&marketplace, &by_seller(BUYER)).unwrap();
+        assert_eq!(res.listings.into_iter().map(|listing| listing.id).collect::<Vec<_>>(), ids(&["4"]));
+
+        let bids = bids_by_bidder(&app, &marketplace, BIDDER).bids;
+        assert_eq!(bids.iter().map(|bid| (bid.token_id.as_str(), bid.amount)).collect::<Vec<_>>(), vec![("1", 50), ("3", 60)]);
+        let msg = QueryMsg::GetBidsByBidder {
+            bidder: BIDDER.to_string(),
+            start_after: Some((COLLECTION.to_string(), "1".to_string())),
+            limit: Some(1),
+            include_expired: None,
+        };
+        let page: BidderBidsResponse = app.wrap().query_wasm_smart(&marketplace, &msg).unwrap();
+        assert_eq!(page.bids.len(), 1);
+        assert_eq!(page.bids[0].token_id, "3");
+    }
+
+    fn sales_by_collection(app: &App, marketplace: &Addr, start_before: Option<u64>, limit: Option<u32>) -> Vec<Sale> {
+        let msg = QueryMsg::GetSalesByCollection { collection: COLLECTION.to_string(), start_before, limit };
+        let res: SalesResponse = app.wrap().query_wasm_smart(marketplace, &msg).unwrap();
+        res.sales
+    }
+
+    #[test]
+    fn sales_are_logged_and_counted_in_the_collection_stats() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        buy_now(&mut app, BUYER, &marketplace, "1", 1_000).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(6 * 86_400));
+
+        let kind = auction(&app, 1, 1, 3_600);
+        list(&mut app, &marketplace, &collection, "2", None, kind);
+        place_bid(&mut app, BIDDER, &marketplace, "2", 400, None).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(3_601));
+        settle(&mut app, &marketplace, "2").unwrap();
+
+        list(&mut app, &marketplace, &collection, "3", Some(1_000), ListingKind::FixedPrice);
+        place_bid(&mut app, BIDDER2, &marketplace, "3", 300, None).unwrap();
+        let accept = ExecuteMsg::AcceptBid { collection: COLLECTION.to_string(), token_id: "3".to_string(), bidder: BIDDER2.to_string() };
+        execute(&mut app, SELLER, &marketplace, &accept, 0).unwrap();
+
+        let stats = collection_stats(&app, &marketplace);
+        assert_eq!((stats.volume_24h, stats.volume_7d, stats.volume_all_time, stats.sales_count), (700, 1_700, 1_700, 3));
+        assert_eq!(stats.floor_price, None);
+
+        // the first buyer resells two days later, pushing the earlier sales out of the windows
+        app.update_block(|block| block.time = block.time.plus_seconds(2 * 86_400));
+        let list_msg = ReceiveNftMsg::List { price: Some(900), kind: ListingKind::FixedPrice, expires: None, reserved_for: None };
+        send_nft(&mut app, BUYER, &marketplace, &collection, "1", &list_msg).unwrap();
+        assert_eq!(collection_stats(&app, &marketplace).floor_price, Some(900));
+        buy_now(&mut app, BIDDER, &marketplace, "1", 900).unwrap();
+
+        let stats = collection_stats(&app, &marketplace);
+        assert_eq!((stats.volume_24h, stats.volume_7d, stats.volume_all_time, stats.sales_count), (900, 1_600, 2_600, 4));
+
+        // most recent sales first, paged with start_before
+        let summary = |sales: &[Sale]| sales.iter().map(|sale| (sale.token_id.clone(), sale.kind.clone(), sale.price)).collect::<Vec<_>>();
+        let page = sales_by_collection(&app, &marketplace, None, Some(2));
+        assert_eq!(summary(&page), vec![("1".to_string(), SaleKind::BuyNow, 900), ("3".to_string(), SaleKind::Bid, 300)]);
+        let page = sales_by_collection(&app, &marketplace, Some(page[1].id), None);
+        assert_eq!(summary(&page), vec![("2".to_string(), SaleKind::Auction, 400), ("1".to_string(), SaleKind::BuyNow, 1_000)]);
+        assert_eq!(page[0].buyer, BIDDER);
+        assert_eq!(page[1].seller, SELLER);
+
+        let msg = QueryMsg::GetSalesByToken { collection: COLLECTION.to_string(), token_id: "1".to_string(), start_before: None, limit: None };
+        let token_sales: SalesResponse = app.wrap().query_wasm_smart(&marketplace, &msg).unwrap();
+        let trail: Vec<_> = token_sales.sales.iter().map(|sale| (sale.seller.as_str(), sale.buyer.as_str(), sale.price)).collect();
+        assert_eq!(trail, vec![(BUYER, BIDDER, 900), (SELLER, BUYER, 1_000)]);
+
+        let last_sale = |token_id: &str| QueryMsg::GetLastSale { collection: COLLECTION.to_string(), token_id: token_id.to_string() };
+        let last: Option<Sale> = app.wrap().query_wasm_smart(&marketplace, &last_sale("1")).unwrap();
+        assert_eq!(last.map(|sale| sale.price), Some(900));
+        let last: Option<Sale> = app.wrap().query_wasm_smart(&marketplace, &last_sale("4")).unwrap();
+        assert!(last.is_none());
+    }
+
+    fn grant(app: &mut App, marketplace: &Addr, role: Role, address: &str) {
+        let msg = ExecuteMsg::GrantRole { role, address: address.to_string() };
+        execute(app, ADMIN, marketplace, &msg, 0).unwrap();
+    }
+
+    fn roles(app: &App, marketplace: &Addr, address: &str) -> Vec<Role> {
+        let res: RolesResponse = app.wrap().query_wasm_smart(marketplace, &QueryMsg::Roles { address: address.to_string() }).unwrap();
+        res.roles
+    }
+
+    #[test]
+    fn owner_grants_and_revokes_marketplace_roles() {
+        let (mut app, marketplace, _) = proper_instantiate(0);
+        let add = |name: &str, app: &mut App| {
+            let contract_addr = instantiate_collection(app, 0);
+            ExecuteMsg::AddApprovedCollection { collection: approved_collection(&contract_addr, name) }
+        };
+
+        let msg = add("first", &mut app);
+        let err = execute(&mut app, CURATOR, &marketplace, &msg, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        grant(&mut app, &marketplace, Role::CollectionCurator, CURATOR);
+        assert_eq!(roles(&app, &marketplace, CURATOR), vec![Role::CollectionCurator]);
+        let members = QueryMsg::RoleMembers { role: Role::CollectionCurator, start_after: None, limit: None };
+        let res: RoleMembersResponse = app.wrap().query_wasm_smart(&marketplace, &members).unwrap();
+        assert_eq!(res.members, vec![Addr::unchecked(CURATOR)]);
+        execute(&mut app, CURATOR, &marketplace, &msg, 0).unwrap();
+
+        // role holders can't hand out roles, not even their own
+        let grant_msg = ExecuteMsg::GrantRole { role: Role::CollectionCurator, address: BUYER.to_string() };
+        let err = execute(&mut app, CURATOR, &marketplace, &grant_msg, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+
+        let revoke = |role| ExecuteMsg::RevokeRole { role, address: CURATOR.to_string() };
+        let err = execute(&mut app, CURATOR, &marketplace, &revoke(Role::CollectionCurator), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        let err = execute(&mut app, ADMIN, &marketplace, &revoke(Role::FeeManager), 0).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+        execute(&mut app, ADMIN, &marketplace, &revoke(Role::CollectionCurator), 0).unwrap();
+        assert!(roles(&app, &marketplace, CURATOR).is_empty());
+
+        let msg = add("second", &mut app);
+        let err = execute(&mut app, CURATOR, &marketplace, &msg, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn marketplace_roles_only_unlock_their_own_actions() {
+        let (mut app, marketplace, collection) = proper_instantiate(0);
+        grant(&mut app, &marketplace, Role::CollectionCurator, CURATOR);
+        grant(&mut app, &marketplace, Role::FeeManager, FEE_MANAGER);
+        grant(&mut app, &marketplace, Role::Moderator, MODERATOR);
+
+        // fees belong to the fee manager
+        let set_fee = ExecuteMsg::SetFee { fee_bps: 100, fee_collector: Some(COLLECTOR.to_string()) };
+        let set_collection_fee = ExecuteMsg::SetCollectionFee { collection: COLLECTION.to_string(), fee_bps: Some(200) };
+        for msg in [&set_fee, &set_collection_fee] {
+            for sender in [CURATOR, MODERATOR] {
+                let err = execute(&mut app, sender, &marketplace, msg, 0).unwrap_err();
+                assert!(matches!(err, ContractError::Unauthorized {}));
+            }
+            execute(&mut app, FEE_MANAGER, &marketplace, msg, 0).unwrap();
+        }
+
+        // collection settings to the curator
+        let set_cap = ExecuteMsg::SetRoyaltyCap { collection: COLLECTION.to_string(), max_royalty_percentage: Some(5) };
+        let pause = ExecuteMsg::SetCollectionPaused { name: COLLECTION.to_string(), paused: false };
+        for msg in [&set_cap, &pause] {
+            for sender in [FEE_MANAGER, MODERATOR] {
+                let err = execute(&mut app, sender, &marketplace, msg, 0).unwrap_err();
+                assert!(matches!(err, ContractError::Unauthorized {}));
+            }
+            execute(&mut app, CURATOR, &marketplace, msg, 0).unwrap();
+        }
+
+        // and delisting to the moderator
+        list(&mut app, &marketplace, &collection, "1", Some(1_000), ListingKind::FixedPrice);
+        let delist = ExecuteMsg::DelistToken { collection_name: COLLECTION.to_string(), token_id: "1".to_string() };
+        for sender in [CURATOR, FEE_MANAGER] {
+            let err = execute(&mut app, sender, &marketplace, &delist, 0).unwrap_err();
+            assert!(matches!(err, ContractError::Unauthorized {}));
+        }
+        execute(&mut app, MODERATOR, &marketplace, &delist, 0).unwrap();
+        assert_eq!(owner_of(&app, &collection, "1"), SELLER);
+    }
+
+    #[test]
+    fn ownership_moves_only_once_the_new_owner_accepts() {
+        let (mut app, marketplace, _) = proper_instantiate(0);
+        let ownership = |app: &App| -> cw_ownable::Ownership<Addr> {
+            app.wrap().query_wasm_smart(&marketplace, &QueryMsg::Ownership {}).unwrap()
+        };
+        let update = |action| ExecuteMsg::UpdateOwnership(action);
+        let transfer = || update(cw_ownable::Action::TransferOwnership { new_owner: COLLECTOR.to_string(), expiry: None });
+
+        let err = execute(&mut app, BUYER, &marketplace, &transfer(), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        execute(&mut app, ADMIN, &marketplace, &transfer(), 0).unwrap();
+        let pending = ownership(&app);
+        assert_eq!(pending.owner, Some(Addr::unchecked(ADMIN)));
+        assert_eq!(pending.pending_owner, Some(Addr::unchecked(COLLECTOR)));
+
+        // until the transfer is accepted the old owner keeps every power
+        grant(&mut app, &marketplace, Role::Guardian, GUARDIAN);
+        let grant_msg = ExecuteMsg::GrantRole { role: Role::Moderator, address: MODERATOR.to_string() };
+        let err = execute(&mut app, COLLECTOR, &marketplace, &grant_msg, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+
+        let err = execute(&mut app, BUYER, &marketplace, &update(cw_ownable::Action::AcceptOwnership), 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        execute(&mut app, COLLECTOR, &marketplace, &update(cw_ownable::Action::AcceptOwnership), 0).unwrap();
+        let accepted = ownership(&app);
+        assert_eq!(accepted.owner, Some(Addr::unchecked(COLLECTOR)));
+        assert_eq!(accepted.pending_owner, None);
+
+        // the previous owner loses the roles it held implicitly
+        let err = execute(&mut app, ADMIN, &marketplace, &grant_msg, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        let set_fee = ExecuteMsg::SetFee { fee_bps: 100, fee_collector: None };
+        let err = execute(&mut app, ADMIN, &marketplace, &set_fee, 0).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(&mut app, COLLECTOR, &marketplace, &grant_msg, 0).unwrap();
+        execute(&mut app, COLLECTOR, &marketplace, &set_fee, 0).unwrap();
+    }
+}
@@ -4,5 +4,249 @@ pub mod helpers;
 pub mod integration_tests;
 pub mod msg;
 pub mod state;
+pub mod upgrades;
 
 pub use crate::error::ContractError;
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{Addr, Order, StdResult, Storage};
+    use cw2::ContractVersion;
+    use cw721::Expiration;
+
+    use crate::contract::{self, CONTRACT_NAME, CONTRACT_VERSION};
+    use crate::msg::{InstantiateMsg, MigrateMsg};
+    use crate::state::{
+        listings, Bid, Collection, Listing, ListingCustody, ListingKind, State, APPROVED_COLLECTIONS, COLLECTIONS_BY_ADDR,
+        LEGACY_BIDS, LEGACY_LISTINGS, STATE,
+    };
+
+    use super::*;
+
+    fn migrate_msg() -> MigrateMsg {
+        MigrateMsg { fee_bps: None, fee_collector: None, denom: None, owner: None }
+    }
+
+    fn legacy_listing(collection: &str, token_id: &str, seller: &str, bids: Option<Vec<Bid>>) -> Listing {
+        Listing {
+            collection: collection.into(),
+            token_id: token_id.into(),
+            token_uri: "".into(),
+            owner: Addr::unchecked(seller),
+            price: Some(100),
+            bids,
+            kind: ListingKind::FixedPrice,
+            expires: None,
+            reserved_for: None,
+            custody: ListingCustody::Escrow,
+            listed_at: None,
+        }
+    }
+
+    fn approved_collection(contract_addr: &str, name: &str) -> Collection {
+        Collection {
+            contract_addr: Addr::unchecked(contract_addr),
+            name: name.into(),
+            max_royalty_percentage: None,
+            fee_bps: None,
+            paused: false,
+        }
+    }
+
+    /// Writes a 0.1.x Marketplace: `state` owner, string-keyed listings and unindexed collections.
+    fn legacy_marketplace(storage: &mut dyn Storage, legacy: &[Listing], collections: &[Collection]) {
+        cw2::set_contract_version(storage, CONTRACT_NAME, upgrades::MIN_MIGRATE_VERSION).unwrap();
+        STATE.save(storage, &State { owner: Addr::unchecked("owner") }).unwrap();
+        for listing in legacy {
+            let key = format!("{}:{}", listing.collection, listing.token_id);
+            LEGACY_LISTINGS.save(storage, &key, listing).unwrap();
+        }
+        for collection in collections {
+            APPROVED_COLLECTIONS.save(storage, &collection.name, collection).unwrap();
+        }
+    }
+
+    /// Make sure cw2 version info is properly initialized during instantiation.
+    #[test]
+    fn proper_cw2_initialization() {
+        let mut deps = mock_dependencies();
+
+        contract::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            InstantiateMsg { count: 0, approved_collections: vec![], denom: None },
+        )
+        .unwrap();
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            version,
+            ContractVersion {
+                contract: CONTRACT_NAME.into(),
+                version: CONTRACT_VERSION.into(),
+            },
+        );
+    }
+
+    /// 0.1.x listings move to the indexed map under their own (collection, token_id), even when the
+    /// collection name contains ':', and are found again through the seller index.
+    #[test]
+    fn migrate_moves_legacy_listings() {
+        let mut deps = mock_dependencies();
+        let chip = legacy_listing("glo:chips", "1", "seller", None);
+        let key = legacy_listing("glo:keys:s1", "7", "seller", None);
+        let other = legacy_listing("glo:chips", "2", "other", None);
+        legacy_marketplace(
+            deps.as_mut().storage,
+            &[chip.clone(), key.clone(), other.clone()],
+            &[approved_collection("chips", "glo:chips"), approved_collection("keys", "glo:keys:s1")],
+        );
+
+        let res = contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+        assert!(res.attributes.iter().any(|attr| attr.key == "from_version" && attr.value == upgrades::MIN_MIGRATE_VERSION));
+        assert!(res.attributes.iter().any(|attr| attr.key == "steps" && attr.value == "0.2.0"));
+
+        assert_eq!(listings().load(deps.as_ref().storage, ("glo:chips", "1")).unwrap(), chip);
+        assert_eq!(listings().load(deps.as_ref().storage, ("glo:keys:s1", "7")).unwrap(), key);
+        assert_eq!(listings().load(deps.as_ref().storage, ("glo:chips", "2")).unwrap(), other);
+        let by_seller = listings()
+            .idx
+            .seller
+            .prefix(Addr::unchecked("seller"))
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, listing)| listing))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(by_seller, vec![chip, key]);
+
+        let legacy = LEGACY_LISTINGS
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert!(legacy.is_empty());
+        assert_eq!(cw2::get_contract_version(deps.as_ref().storage).unwrap().version, CONTRACT_VERSION);
+    }
+
+    /// Bids inside a 0.1.x listing were never escrowed, so they are held for the owner and the
+    /// migrated listing carries no refundable bids.
+    #[test]
+    fn migrate_holds_legacy_bids() {
+        let mut deps = mock_dependencies();
+        let first = Bid { bidder: Addr::unchecked("bidder"), amount: 50, expires: None };
+        let second = Bid { bidder: Addr::unchecked("late_bidder"), amount: 60, expires: Some(Expiration::AtHeight(10)) };
+        legacy_marketplace(
+            deps.as_mut().storage,
+            &[legacy_listing("glo:chips", "1", "seller", Some(vec![first.clone(), second.clone()]))],
+            &[approved_collection("chips", "glo:chips")],
+        );
+
+        contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+        let listing = listings().load(deps.as_ref().storage, ("glo:chips", "1")).unwrap();
+        assert_eq!(listing, legacy_listing("glo:chips", "1", "seller", None));
+        let held = LEGACY_BIDS
+            .prefix(("glo:chips", "1"))
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, bid)| bid))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(held, vec![first, second]);
+    }
+
+    /// Approved collections are indexed by address, and a 0.1.x owner moves to cw_ownable.
+    #[test]
+    fn migrate_indexes_collections_and_moves_owner() {
+        let mut deps = mock_dependencies();
+        let collection = approved_collection("chips", "glo:chips");
+        legacy_marketplace(deps.as_mut().storage, &[], &[collection.clone()]);
+
+        contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+        let indexed = COLLECTIONS_BY_ADDR.load(deps.as_ref().storage, &collection.contract_addr).unwrap();
+        assert_eq!(indexed, collection.name);
+        let ownership = cw_ownable::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked("owner")));
+        assert!(STATE.may_load(deps.as_ref().storage).unwrap().is_none());
+    }
+
+    /// An owner already set through cw_ownable wins over the stale `state` item.
+    #[test]
+    fn migrate_keeps_existing_owner() {
+        let mut deps = mock_dependencies();
+        legacy_marketplace(deps.as_mut().storage, &[], &[]);
+        cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some("new_owner")).unwrap();
+
+        contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+        let ownership = cw_ownable::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked("new_owner")));
+        assert!(STATE.may_load(deps.as_ref().storage).unwrap().is_none());
+    }
+
+    /// 0.1.x could approve one contract under two names; its NFTs would be ambiguous, so migration stops.
+    #[test]
+    fn migrate_rejects_collections_sharing_an_address() {
+        let mut deps = mock_dependencies();
+        legacy_marketplace(
+            deps.as_mut().storage,
+            &[],
+            &[approved_collection("chips", "glo:chips"), approved_collection("chips", "glo:chips_v2")],
+        );
+
+        let err = contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateCollectionAddress { contract_addr, .. } if contract_addr == "chips"));
+    }
+
+    /// A newer stored version must not be overwritten by older code.
+    #[test]
+    fn migrate_rejects_downgrade() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+
+        let err = contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert!(matches!(err, ContractError::CannotDowngrade { .. }));
+    }
+
+    /// Migrating over a collection contract instead of a Marketplace is refused.
+    #[test]
+    fn migrate_rejects_other_contract() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw721-base", CONTRACT_VERSION).unwrap();
+
+        let err = contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert!(matches!(err, ContractError::Version(cw2::VersionError::WrongContract { .. })));
+    }
+
+    /// Versions older than the first supported one have no upgrade path.
+    #[test]
+    fn migrate_rejects_unsupported_version() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        let err = contract::migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert!(matches!(err, ContractError::UnsupportedMigration { from } if from == "0.0.1"));
+    }
+
+    /// A 0.1.x Marketplace needs the 0.2.0 layout change, and one already on this release needs nothing.
+    #[test]
+    fn pending_steps_follow_the_stored_version() {
+        let current = upgrades::parse_version(CONTRACT_VERSION).unwrap();
+        let legacy = upgrades::parse_version(upgrades::MIN_MIGRATE_VERSION).unwrap();
+
+        let pending: Vec<_> = upgrades::pending_steps(&legacy, &current).unwrap().iter().map(|step| step.version).collect();
+        assert_eq!(pending, vec!["0.2.0"]);
+        assert!(upgrades::pending_steps(&current, &current).unwrap().is_empty());
+    }
+
+    /// Upgrade steps must stay in version order for chaining to work.
+    #[test]
+    fn migration_steps_are_ordered() {
+        let versions: Vec<_> = upgrades::MIGRATIONS
+            .iter()
+            .map(|step| upgrades::parse_version(step.version).unwrap())
+            .collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
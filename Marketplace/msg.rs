@@ -1,27 +1,107 @@
-use cw721::Cw721ReceiveMsg;
+use cw721::{Cw721ReceiveMsg, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::Addr;
-use crate::state::{ Collection, Bid };
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use crate::state::{ Collection, Role, PauseScope, Bid, ListingKind, ListingCustody, CollectionOffer, TraitFilter, TokenOffer, TokenRef, Bundle, Trade, Sale };
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub count: i32,
     pub approved_collections: Vec<Collection>,
+    pub denom: Option<String>, // Defaults to uluna
 } 
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    UpdateOwnership(cw_ownable::Action),
+    // Exit paths such as delisting, bid removal and cancellations are never paused
+    Pause { scopes: Vec<PauseScope> },
+    Unpause { scopes: Vec<PauseScope> },
+    GrantRole { role: Role, address: String },
+    RevokeRole { role: Role, address: String },
     AddApprovedCollection { collection: Collection },
+    // Removing, renaming or moving a collection is refused while anything on the Marketplace references it
+    RemoveApprovedCollection { name: String },
+    // Curator or moderator. Closes up to `limit` listings, offers, bundles and trades of the collection with
+    // full refunds, repeat until the `in_use` attribute is false
+    ClearCollection { name: String, limit: Option<u32> },
+    RenameCollection { name: String, new_name: String },
+    UpdateCollectionAddress { name: String, contract_addr: String },
+    // Paused collections keep their listings, but only delisting, bid removal and cancellations go through
+    SetCollectionPaused { name: String, paused: bool },
     DelistToken { collection_name: String, token_id: String },
-    PlaceBid { collection: String, token_id: String, amount: u128 },
+    PlaceBid { collection: String, token_id: String, amount: u128, expires: Option<Expiration> },
     RemoveBid { collection: String, token_id: String },
     ReceiveNft(Cw721ReceiveMsg),
     BuyNow { collection: String, token_id: String },
     AcceptBid { collection: String, token_id: String, bidder: String },
     EditListing { collection: String, token_id: String, new_amount: u128, },
     RemoveListing { collection: String, token_id: String },
+    SetRoyaltyCap { collection: String, max_royalty_percentage: Option<u64> },
+    SetFee { fee_bps: u64, fee_collector: Option<String> },
+    SetCollectionFee { collection: String, fee_bps: Option<u64> },
+    WithdrawFees { amount: Option<u128>, recipient: Option<String> },
+    // Owner only. Refunds a bid held back by the 0.2.0 migration once it has been matched to a
+    // paid 0.1.x PlaceBid, or discards it otherwise
+    ResolveLegacyBid { collection: String, token_id: String, bidder: String, refund: bool },
+    SettleAuction { collection: String, token_id: String },
+    RemoveExpiredBids { collection: String, token_id: String },
+    ReturnExpiredListing { collection: String, token_id: String },
+    CreateCollectionOffer { collection: String, filter: Option<TraitFilter>, price: u128, quantity: u32, expires: Option<Expiration> },
+    CancelCollectionOffer { offer_id: u64 },
+    MakeOffer { collection: String, token_id: String, amount: u128, expires: Option<Expiration> },
+    CancelOffer { collection: String, token_id: String },
+    ListWithApproval {
+        collection: String,
+        token_id: String,
+        price: Option<u128>,
+        #[serde(default)]
+        kind: ListingKind,
+        expires: Option<Expiration>,
+        reserved_for: Option<String>,
+    },
+    InvalidateListing { collection: String, token_id: String },
+    CreateBundle { items: Vec<TokenRef>, price: Option<u128>, expires: Option<Expiration> },
+    CancelBundle { bundle_id: u64 },
+    BuyBundle { bundle_id: u64 },
+    PlaceBundleBid { bundle_id: u64, amount: u128, expires: Option<Expiration> },
+    RemoveBundleBid { bundle_id: u64 },
+    AcceptBundleBid { bundle_id: u64, bidder: String },
+    // The offered funds are attached to the message
+    ProposeTrade {
+        counterparty: String,
+        offered: Vec<TokenRef>,
+        requested: Vec<TokenRef>,
+        requested_funds: u128,
+        expires: Option<Expiration>,
+    },
+    AcceptTrade { trade_id: u64 },
+    CancelTrade { trade_id: u64 },
+    // Unavailable or overpriced listings revert the whole batch unless skip_unavailable is set
+    BatchBuy { items: Vec<BatchBuyItem>, skip_unavailable: bool },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchBuyItem {
+    pub collection: String,
+    pub token_id: String,
+    pub max_price: u128,
+}
+
+/// Payloads accepted in the `msg` of a cw721 `SendNft` to the Marketplace
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveNftMsg {
+    List {
+        price: Option<u128>,
+        #[serde(default)]
+        kind: ListingKind,
+        expires: Option<Expiration>,
+        reserved_for: Option<String>,
+    },
+    FillCollectionOffer { offer_id: u64 },
+    AcceptOffer { bidder: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -29,9 +109,45 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     GetListingDetails { collection: String, token_id: String },
     GetStateOwner {},
-    GetBidDetails{ collection: String, token_id: String},
-    GetListings { start_after: Option<String>, limit: Option<u32>,
+    Ownership {},
+    PauseState {},
+    GetDenom {},
+    Roles { address: String },
+    RoleMembers { role: Role, start_after: Option<String>, limit: Option<u32> },
+    ApprovedCollections { start_after: Option<String>, limit: Option<u32> },
+    LegacyBids { start_after: Option<(String, String, String)>, limit: Option<u32> },
+    SearchListings {
+        filter: Option<ListingFilter>,
+        sort_by: Option<ListingSort>, // Key order if None
+        descending: Option<bool>,
+        start_after: Option<ListingCursor>,
+        limit: Option<u32>,
+    },
+    // Includes the seller's private listings
+    GetListingsBySeller { seller: String, start_after: Option<(String, String)>, limit: Option<u32>, include_expired: Option<bool> },
+    GetBidsByBidder { bidder: String, start_after: Option<(String, String)>, limit: Option<u32>, include_expired: Option<bool> },
+    GetBidDetails{ collection: String, token_id: String, include_expired: Option<bool> },
+    GetListings { start_after: Option<(String, String)>, limit: Option<u32>, include_expired: Option<bool>,
+        // Private listings are only returned to a query for their reserved buyer
+        reserved_for: Option<String>,
     },
+    GetFees {},
+    GetDutchPrice { collection: String, token_id: String, time: Option<Timestamp> },
+    GetCollectionOffer { offer_id: u64 },
+    GetCollectionOffers { collection: Option<String>, start_after: Option<u64>, limit: Option<u32>, include_expired: Option<bool> },
+    GetTokenOffers { collection: String, token_id: String, start_after: Option<String>, limit: Option<u32>, include_expired: Option<bool> },
+    // start_after is the (collection, token_id) of the last offer returned
+    GetBundle { bundle_id: u64 },
+    GetBundles { start_after: Option<u64>, limit: Option<u32>, include_expired: Option<bool> },
+    // Sales are returned most recent first, start_before is a sale id
+    GetSalesByCollection { collection: String, start_before: Option<u64>, limit: Option<u32> },
+    GetSalesByToken { collection: String, token_id: String, start_before: Option<u64>, limit: Option<u32> },
+    GetLastSale { collection: String, token_id: String },
+    GetCollectionStats { collection: String },
+    GetTrade { trade_id: u64 },
+    // Trades where `party` is either the maker or the counterparty
+    GetTrades { party: Option<String>, start_after: Option<u64>, limit: Option<u32>, include_expired: Option<bool> },
+    GetOffersByBidder { bidder: String, start_after: Option<(String, String)>, limit: Option<u32>, include_expired: Option<bool> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,6 +157,56 @@ pub struct ListingResponse {
     pub owner: Addr,
     pub price: u128,
     pub bids: Vec<Bid>,
+    pub kind: ListingKind,
+    pub expires: Option<Expiration>,
+    pub reserved_for: Option<Addr>,
+    pub custody: ListingCustody,
+    pub listed_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ListingFilter {
+    pub collection: Option<String>,
+    pub seller: Option<String>,
+    pub min_price: Option<u128>,
+    pub max_price: Option<u128>,
+    pub has_bids: Option<bool>,
+    pub include_expired: Option<bool>,
+    pub reserved_for: Option<String>, // Private listings are only returned for their reserved buyer
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingSort {
+    Price,
+    ListedAt,
+}
+
+/// Position of the last listing returned, passed back as `start_after` for the next page
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListingCursor {
+    pub sort_value: u128, // Price or listing time in nanoseconds, unused in key order
+    pub collection: String,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListingsPageResponse {
+    pub listings: Vec<ListingResponse>,
+    pub next: Option<ListingCursor>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderBid {
+    pub collection: String,
+    pub token_id: String,
+    pub amount: u128,
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderBidsResponse {
+    pub bids: Vec<BidderBid>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -48,6 +214,34 @@ pub struct ListingsResponse {
     pub listings: Vec<ListingResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembersResponse {
+    pub role: Role,
+    pub members: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionsResponse {
+    pub collections: Vec<Collection>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyBid {
+    pub collection: String,
+    pub token_id: String,
+    pub bid: Bid,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyBidsResponse {
+    pub bids: Vec<LegacyBid>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BidResponse { 
     pub token_id: String,
@@ -55,4 +249,100 @@ pub struct BidResponse {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
\ No newline at end of file
+pub struct CollectionOffersResponse {
+    pub offers: Vec<CollectionOffer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenOffersResponse {
+    pub offers: Vec<TokenOffer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BundlesResponse {
+    pub bundles: Vec<Bundle>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TradesResponse {
+    pub trades: Vec<Trade>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SalesResponse {
+    pub sales: Vec<Sale>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionStatsResponse {
+    pub floor_price: Option<u128>, // Cheapest public fixed-price or live Dutch listing
+    pub volume_24h: u128,
+    pub volume_7d: u128,
+    pub volume_all_time: u128,
+    pub sales_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DutchPriceResponse {
+    pub price: u128,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesResponse {
+    pub fee_bps: u64,
+    pub fee_collector: Option<Addr>,
+    pub collected: u128,
+    pub accrued: u128,
+    pub withdrawn: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    pub fee_bps: Option<u64>,
+    pub fee_collector: Option<String>,
+    // Only accepted while the contract holds none of the current denom
+    pub denom: Option<String>,
+    pub owner: Option<String>,
+}
+
+/// Queries the Marketplace sends to approved collections
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionQueryMsg {
+    Extension { msg: Cw2981QueryMsg },
+    TokenTraits { token_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw2981QueryMsg {
+    RoyaltyInfo { token_id: String, sale_price: Uint128 },
+    CheckRoyalties {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+/// The part of a cw721 `NftInfo` response the Marketplace reads, the extension is ignored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftInfoResponse {
+    pub token_uri: Option<String>,
+}
+
+/// Catalog traits of a token, as reported by the Minter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenTraitsResponse {
+    pub base_id: String,
+    pub rarity: Option<String>,
+    pub season_id: Option<String>,
+    pub special: bool,
+}
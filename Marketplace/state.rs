@@ -1,15 +1,84 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, BlockInfo, Empty, StdResult, Storage, Timestamp, Uint128};
+use cw721::Expiration;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseScope {
+    Listings, // New listings, bundles and trade proposals
+    Bids, // Bids and offers of every kind
+    Purchases, // Anything that settles a sale or trade, except ended auctions
+}
+
+impl PauseScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseScope::Listings => "listings",
+            PauseScope::Bids => "bids",
+            PauseScope::Purchases => "purchases",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseState {
+    pub listings: bool,
+    pub bids: bool,
+    pub purchases: bool,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, scope: PauseScope) -> bool {
+        match scope {
+            PauseScope::Listings => self.listings,
+            PauseScope::Bids => self.bids,
+            PauseScope::Purchases => self.purchases,
+        }
+    }
+
+    pub fn set(&mut self, scope: PauseScope, paused: bool) {
+        match scope {
+            PauseScope::Listings => self.listings = paused,
+            PauseScope::Bids => self.bids = paused,
+            PauseScope::Purchases => self.purchases = paused,
+        }
+    }
+}
+
+// Layout used before ownership moved to cw_ownable, only read by migrate
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub owner: Addr,
-    pub approved_collections: Vec<Collection>,
 } 
 
+/// Admin roles the owner can hand out. The owner passes every role check.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    CollectionCurator, // Approved collections, their pause flag and royalty caps
+    FeeManager, // Marketplace and per-collection fees, and fee withdrawals
+    Moderator, // Force-removal of listings
+    Guardian, // Can pause trading, but not resume it
+}
+
+impl Role {
+    pub fn all() -> Vec<Role> {
+        vec![Role::CollectionCurator, Role::FeeManager, Role::Moderator, Role::Guardian]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::CollectionCurator => "collection_curator",
+            Role::FeeManager => "fee_manager",
+            Role::Moderator => "moderator",
+            Role::Guardian => "guardian",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)] 
 pub struct Listing {
     pub collection: String,
@@ -18,20 +87,478 @@ pub struct Listing {
     pub owner: Addr,
     pub price: Option<u128>,
     pub bids: Option<Vec<Bid>>,
+    #[serde(default)]
+    pub kind: ListingKind,
+    pub expires: Option<Expiration>,
+    pub reserved_for: Option<Addr>, // Only this address can buy when set
+    #[serde(default)]
+    pub custody: ListingCustody,
+    pub listed_at: Option<Timestamp>, // None for listings created before it was recorded
+}
+
+/// Shared by everything on the Marketplace that can lapse, `None` never expires
+pub fn is_expired(expires: Option<Expiration>, block: &BlockInfo) -> bool {
+    expires.is_some_and(|expires| expires.is_expired(block))
+}
+
+impl Listing {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        is_expired(self.expires, block)
+    }
+
+    /// Price the listing is indexed under: the fixed price, an auction's high bid or reserve,
+    /// or a Dutch auction's floor price
+    pub fn index_price(&self) -> u128 {
+        match &self.kind {
+            ListingKind::FixedPrice => self.price.unwrap_or_default(),
+            ListingKind::Auction(auction) => auction.highest_bid.as_ref().map_or(auction.reserve_price, |bid| bid.amount),
+            ListingKind::Dutch(dutch) => dutch.floor_price,
+        }
+    }
+
+    /// Listing time in nanoseconds, 0 for listings created before it was recorded
+    pub fn index_listed_at(&self) -> u64 {
+        self.listed_at.map_or(0, |time| time.nanos())
+    }
+
+    /// Every address with a bid escrowed on the listing
+    pub fn bidders(&self) -> Vec<&Addr> {
+        let mut bidders: Vec<&Addr> = self.bids.iter().flatten().map(|bid| &bid.bidder).collect();
+        if let ListingKind::Auction(Auction { highest_bid: Some(bid), .. }) = &self.kind {
+            bidders.push(&bid.bidder);
+        }
+        bidders
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingKind {
+    #[default]
+    FixedPrice,
+    Auction(Auction),
+    Dutch(DutchAuction),
+}
+
+/// Where a listed NFT is held until it sells
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingCustody {
+    /// Sent to the Marketplace with SendNft
+    #[default]
+    Escrow,
+    /// Kept by the seller, who approved the Marketplace to transfer it on sale
+    Approval,
+}
+
+/// English auction: ascending bids, the highest bid at `end_time` wins if it meets the reserve
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub start_time: Timestamp,
+    pub end_time: Timestamp, // Pushed back when a bid lands close to the end
+    pub reserve_price: u128,
+    pub min_increment: u128,
+    pub highest_bid: Option<Bid>,
+}
+
+/// Dutch auction: the price falls from `start_price` to `floor_price` between `start_time` and `end_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DutchAuction {
+    pub start_price: u128,
+    pub floor_price: u128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub step_seconds: Option<u64>, // Price drops once per step, or continuously if None
+}
+
+impl DutchAuction {
+    pub fn price_at(&self, time: Timestamp) -> u128 {
+        if time <= self.start_time {
+            return self.start_price;
+        }
+        if time >= self.end_time {
+            return self.floor_price;
+        }
+
+        let duration = self.end_time.seconds() - self.start_time.seconds();
+        let mut elapsed = time.seconds() - self.start_time.seconds();
+        if let Some(step) = self.step_seconds.filter(|step| *step > 0) {
+            elapsed -= elapsed % step;
+        }
+
+        let decline = Uint128::from(self.start_price - self.floor_price).multiply_ratio(elapsed, duration);
+        self.start_price - decline.u128()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Collection {
     pub contract_addr: Addr,
     pub name: String,
+    pub max_royalty_percentage: Option<u64>, // Cap on cw2981 royalties (0-100), uncapped if None
+    pub fee_bps: Option<u64>, // Overrides the marketplace fee for this collection
+    #[serde(default)]
+    pub paused: bool, // No new listings, offers, bids or sales while paused
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Bid {
     pub bidder: Addr, // New field for bidder's address
     pub amount: u128, // New field for bid amount
+    pub expires: Option<Expiration>,
+}
+
+impl Bid {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        is_expired(self.expires, block)
+    }
+}
+
+/// Escrowed offer for up to `quantity` tokens of a collection, filled one token at a time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionOffer {
+    pub id: u64,
+    pub bidder: Addr,
+    pub collection: String,
+    pub filter: Option<TraitFilter>,
+    pub price: u128, // Paid per token
+    pub quantity: u32,
+    pub filled: u32,
+    pub expires: Option<Expiration>,
+}
+
+impl CollectionOffer {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        is_expired(self.expires, block)
+    }
+}
+
+/// Escrowed offer on a single token, which does not have to be listed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenOffer {
+    pub collection: String,
+    pub token_id: String,
+    pub bidder: Addr,
+    pub amount: u128,
+    pub expires: Option<Expiration>,
+}
+
+impl TokenOffer {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        is_expired(self.expires, block)
+    }
+}
+
+/// Token traits a collection offer is restricted to, as reported by the Minter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitFilter {
+    pub base_id: Option<String>,
+    pub rarity: Option<String>,
+    pub season_id: Option<String>,
+}
+
+/// Identifies one NFT of an approved collection
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenRef {
+    pub collection: String,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BundleItem {
+    pub collection: String,
+    pub token_id: String,
+    pub token_uri: String,
+}
+
+/// Several escrowed NFTs sold together for one price
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Bundle {
+    pub id: u64,
+    pub owner: Addr,
+    pub items: Vec<BundleItem>,
+    pub price: Option<u128>, // Only open to bids if None
+    pub bids: Vec<Bid>,
+    pub expires: Option<Expiration>,
+}
+
+impl Bundle {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        is_expired(self.expires, block)
+    }
+}
+
+/// Swap proposed by `maker` to `counterparty`, the maker's side is escrowed until it is accepted or cancelled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Trade {
+    pub id: u64,
+    pub maker: Addr,
+    pub counterparty: Addr,
+    pub offered: Vec<TokenRef>,
+    pub offered_funds: u128,
+    pub requested: Vec<TokenRef>,
+    pub requested_funds: u128,
+    pub expires: Option<Expiration>,
+}
+
+impl Trade {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        is_expired(self.expires, block)
+    }
+}
+
+/// A completed sale, kept after its listing or offer is gone
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Sale {
+    pub id: u64,
+    pub collection: String,
+    pub token_id: String,
+    pub seller: Addr,
+    pub buyer: Addr,
+    pub price: u128, // A bundle item's share of the bundle price
+    pub kind: SaleKind,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SaleKind {
+    BuyNow,
+    Bid,
+    Auction,
+    Offer,
+    CollectionOffer,
+    Bundle,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct CollectionStats {
+    pub sales: u64,
+    pub volume: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FeeConfig {
+    pub fee_bps: u64, // Marketplace fee in basis points (0-10000) of the sale price
+    pub fee_collector: Option<Addr>, // Fees accrue in the contract when no collector is set
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FeeTotals {
+    pub collected: u128, // Every fee charged, whether sent to the collector or accrued
+    pub accrued: u128, // Fees held by the contract until withdrawn
+    pub withdrawn: u128,
 }
 
 pub const STATE: Item<State> = Item::new("state");
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+pub const PAUSE_STATE: Item<PauseState> = Item::new("pause_state");
+
+// Contracts deployed before the denom was configurable trade in uluna
+pub const DEFAULT_DENOM: &str = "uluna";
+pub const PAYMENT_DENOM: Item<String> = Item::new("payment_denom");
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+pub const FEE_TOTALS: Item<FeeTotals> = Item::new("fee_totals");
 pub const APPROVED_COLLECTIONS: Map<&str, Collection> = Map::new("approved_collections");
-pub const LISTINGS: Map<&str, Listing> = Map::new("new_listings:");
\ No newline at end of file
+// Name of the approved collection behind each contract address
+pub const COLLECTIONS_BY_ADDR: Map<&Addr, String> = Map::new("collections_by_addr");
+// Listings keyed by "collection:token_id", moved into `listings()` by migrate
+pub const LEGACY_LISTINGS: Map<&str, Listing> = Map::new("new_listings:");
+// Bids carried by 0.1.x listings, keyed by (collection, token_id, bidder). The listing payload could
+// carry bids that were never paid for, so these stay out of every refund path until the owner resolves them
+pub const LEGACY_BIDS: Map<(&str, &str, &Addr), Bid> = Map::new("legacy_bids");
+pub const BUNDLES: Map<u64, Bundle> = Map::new("bundles");
+pub const BUNDLE_COUNT: Item<u64> = Item::new("bundle_count");
+pub const TRADES: Map<u64, Trade> = Map::new("trades");
+pub const TRADE_COUNT: Item<u64> = Item::new("trade_count");
+pub const SALE_COUNT: Item<u64> = Item::new("sale_count");
+pub const COLLECTION_STATS: Map<&str, CollectionStats> = Map::new("collection_stats");
+pub const COLLECTION_OFFERS: Map<u64, CollectionOffer> = Map::new("collection_offers");
+pub const COLLECTION_OFFER_COUNT: Item<u64> = Item::new("collection_offer_count");
+// Ids of the collection offers, bundles and trades touching each collection, kept by the save and remove helpers below
+pub const COLLECTION_OFFERS_BY_COLLECTION: Map<(&str, u64), Empty> = Map::new("collection_offers_by_collection");
+pub const BUNDLES_BY_COLLECTION: Map<(&str, u64), Empty> = Map::new("bundles_by_collection");
+pub const TRADES_BY_COLLECTION: Map<(&str, u64), Empty> = Map::new("trades_by_collection");
+
+/// Token offers are keyed by (collection, token_id, bidder) and indexed by bidder
+pub type TokenOfferKey<'a> = (&'a str, &'a str, &'a Addr);
+
+pub struct TokenOfferIndexes<'a> {
+    pub bidder: MultiIndex<'a, Addr, TokenOffer, TokenOfferKey<'a>>,
+}
+
+impl<'a> IndexList<TokenOffer> for TokenOfferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenOffer>> + '_> {
+        let v: Vec<&dyn Index<TokenOffer>> = vec![&self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn token_offers<'a>() -> IndexedMap<'a, TokenOfferKey<'a>, TokenOffer, TokenOfferIndexes<'a>> {
+    let indexes = TokenOfferIndexes {
+        bidder: MultiIndex::new(|_pk, offer| offer.bidder.clone(), "token_offers", "token_offers__bidder"),
+    };
+    IndexedMap::new("token_offers", indexes)
+}
+
+/// Listings are keyed by (collection, token_id) and indexed by seller, by collection, and by price and
+/// listing time both overall and within a collection
+pub type ListingKey<'a> = (&'a str, &'a str);
+
+pub struct ListingIndexes<'a> {
+    pub seller: MultiIndex<'a, Addr, Listing, ListingKey<'a>>,
+    pub collection: MultiIndex<'a, String, Listing, ListingKey<'a>>,
+    pub price: MultiIndex<'a, u128, Listing, ListingKey<'a>>,
+    pub listed_at: MultiIndex<'a, u64, Listing, ListingKey<'a>>,
+    pub collection_price: MultiIndex<'a, (String, u128), Listing, ListingKey<'a>>,
+    pub collection_listed_at: MultiIndex<'a, (String, u64), Listing, ListingKey<'a>>,
+}
+
+impl<'a> IndexList<Listing> for ListingIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Listing>> + '_> {
+        let v: Vec<&dyn Index<Listing>> = vec![
+            &self.seller,
+            &self.collection,
+            &self.price,
+            &self.listed_at,
+            &self.collection_price,
+            &self.collection_listed_at,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn listings<'a>() -> IndexedMap<'a, ListingKey<'a>, Listing, ListingIndexes<'a>> {
+    let indexes = ListingIndexes {
+        seller: MultiIndex::new(|_pk, listing| listing.owner.clone(), "listings", "listings__seller"),
+        collection: MultiIndex::new(|_pk, listing| listing.collection.clone(), "listings", "listings__collection"),
+        price: MultiIndex::new(|_pk, listing| listing.index_price(), "listings", "listings__price"),
+        listed_at: MultiIndex::new(|_pk, listing| listing.index_listed_at(), "listings", "listings__listed_at"),
+        collection_price: MultiIndex::new(
+            |_pk, listing| (listing.collection.clone(), listing.index_price()),
+            "listings",
+            "listings__collection_price",
+        ),
+        collection_listed_at: MultiIndex::new(
+            |_pk, listing| (listing.collection.clone(), listing.index_listed_at()),
+            "listings",
+            "listings__collection_listed_at",
+        ),
+    };
+    IndexedMap::new("listings", indexes)
+}
+
+/// Saves a collection along with its entry in `COLLECTIONS_BY_ADDR`
+pub fn save_collection(storage: &mut dyn Storage, collection: &Collection) -> StdResult<()> {
+    COLLECTIONS_BY_ADDR.save(storage, &collection.contract_addr, &collection.name)?;
+    APPROVED_COLLECTIONS.save(storage, &collection.name, collection)
+}
+
+pub fn remove_collection(storage: &mut dyn Storage, collection: &Collection) {
+    COLLECTIONS_BY_ADDR.remove(storage, &collection.contract_addr);
+    APPROVED_COLLECTIONS.remove(storage, &collection.name);
+}
+
+// Listings each bidder has a bid on, keyed by (bidder, collection, token_id)
+pub const BIDS_BY_BIDDER: Map<(&Addr, &str, &str), Empty> = Map::new("bids_by_bidder");
+
+/// Saves a listing and keeps `BIDS_BY_BIDDER` in step with its bids
+pub fn save_listing(storage: &mut dyn Storage, listing: &Listing) -> StdResult<()> {
+    let key = (listing.collection.as_str(), listing.token_id.as_str());
+    if let Some(previous) = listings().may_load(storage, key)? {
+        for bidder in previous.bidders() {
+            BIDS_BY_BIDDER.remove(storage, (bidder, key.0, key.1));
+        }
+    }
+    for bidder in listing.bidders() {
+        BIDS_BY_BIDDER.save(storage, (bidder, key.0, key.1), &Empty {})?;
+    }
+    listings().save(storage, key, listing)
+}
+
+/// Removes a listing along with its entries in `BIDS_BY_BIDDER`
+pub fn remove_listing(storage: &mut dyn Storage, listing: &Listing) -> StdResult<()> {
+    let key = (listing.collection.as_str(), listing.token_id.as_str());
+    for bidder in listing.bidders() {
+        BIDS_BY_BIDDER.remove(storage, (bidder, key.0, key.1));
+    }
+    listings().remove(storage, key)
+}
+
+/// Saves a collection offer along with its entry in `COLLECTION_OFFERS_BY_COLLECTION`
+pub fn save_collection_offer(storage: &mut dyn Storage, offer: &CollectionOffer) -> StdResult<()> {
+    COLLECTION_OFFERS_BY_COLLECTION.save(storage, (&offer.collection, offer.id), &Empty {})?;
+    COLLECTION_OFFERS.save(storage, offer.id, offer)
+}
+
+pub fn remove_collection_offer(storage: &mut dyn Storage, offer: &CollectionOffer) {
+    COLLECTION_OFFERS_BY_COLLECTION.remove(storage, (&offer.collection, offer.id));
+    COLLECTION_OFFERS.remove(storage, offer.id);
+}
+
+/// Saves a bundle along with an entry in `BUNDLES_BY_COLLECTION` for every collection it holds
+pub fn save_bundle(storage: &mut dyn Storage, bundle: &Bundle) -> StdResult<()> {
+    for item in &bundle.items {
+        BUNDLES_BY_COLLECTION.save(storage, (&item.collection, bundle.id), &Empty {})?;
+    }
+    BUNDLES.save(storage, bundle.id, bundle)
+}
+
+pub fn remove_bundle(storage: &mut dyn Storage, bundle: &Bundle) {
+    for item in &bundle.items {
+        BUNDLES_BY_COLLECTION.remove(storage, (&item.collection, bundle.id));
+    }
+    BUNDLES.remove(storage, bundle.id);
+}
+
+/// Saves a trade along with an entry in `TRADES_BY_COLLECTION` for every collection on either side
+pub fn save_trade(storage: &mut dyn Storage, trade: &Trade) -> StdResult<()> {
+    for item in trade.offered.iter().chain(&trade.requested) {
+        TRADES_BY_COLLECTION.save(storage, (&item.collection, trade.id), &Empty {})?;
+    }
+    TRADES.save(storage, trade.id, trade)
+}
+
+pub fn remove_trade(storage: &mut dyn Storage, trade: &Trade) {
+    for item in trade.offered.iter().chain(&trade.requested) {
+        TRADES_BY_COLLECTION.remove(storage, (&item.collection, trade.id));
+    }
+    TRADES.remove(storage, trade.id);
+}
+
+/// Sales are keyed by a sequential id and indexed by collection and by token
+pub struct SaleIndexes<'a> {
+    pub collection: MultiIndex<'a, String, Sale, u64>,
+    pub token: MultiIndex<'a, (String, String), Sale, u64>,
+}
+
+impl<'a> IndexList<Sale> for SaleIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Sale>> + '_> {
+        let v: Vec<&dyn Index<Sale>> = vec![&self.collection, &self.token];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn sales<'a>() -> IndexedMap<'a, u64, Sale, SaleIndexes<'a>> {
+    let indexes = SaleIndexes {
+        collection: MultiIndex::new(|_pk, sale| sale.collection.clone(), "sales", "sales__collection"),
+        token: MultiIndex::new(|_pk, sale| (sale.collection.clone(), sale.token_id.clone()), "sales", "sales__token"),
+    };
+    IndexedMap::new("sales", indexes)
+}
+
+/// Appends a sale to the log, assigning its id, and adds it to the collection's totals
+pub fn record_sale(storage: &mut dyn Storage, mut sale: Sale) -> StdResult<u64> {
+    sale.id = SALE_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    SALE_COUNT.save(storage, &sale.id)?;
+
+    COLLECTION_STATS.update(storage, &sale.collection, |stats| -> StdResult<_> {
+        let mut stats = stats.unwrap_or_default();
+        stats.sales += 1;
+        stats.volume += sale.price;
+        Ok(stats)
+    })?;
+
+    sales().save(storage, sale.id, &sale)?;
+    Ok(sale.id)
+}
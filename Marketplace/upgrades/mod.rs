@@ -0,0 +1,88 @@
+use cosmwasm_std::{DepsMut, Response};
+use semver::Version;
+
+use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
+use crate::ContractError;
+
+pub mod v0_2_0;
+
+/// First Marketplace release with cw2 version info, so the oldest one a migration can start from
+pub const MIN_MIGRATE_VERSION: &str = "0.1.0";
+
+/// Moves Marketplace storage written by older releases into the layout `version` reads
+pub struct MigrationStep {
+    pub version: &'static str,
+    pub migrate: fn(DepsMut) -> Result<(), ContractError>,
+}
+
+/// Releases that changed the Marketplace storage layout, oldest first.
+/// Releases that only changed code need no entry here.
+pub const MIGRATIONS: &[MigrationStep] = &[
+    // 0.1.x listings, bids, collections and owner moved to the indexed layout
+    MigrationStep { version: "0.2.0", migrate: v0_2_0::migrate },
+];
+
+pub fn parse_version(version: &str) -> Result<Version, ContractError> {
+    version
+        .parse()
+        .map_err(|_| ContractError::InvalidVersion { version: version.to_string() })
+}
+
+/// Steps a Marketplace stored at `from` still needs to read like one built at `to`
+pub fn pending_steps(from: &Version, to: &Version) -> Result<Vec<&'static MigrationStep>, ContractError> {
+    let mut pending = vec![];
+    for step in MIGRATIONS {
+        let version = parse_version(step.version)?;
+        // A step above the crate version would never run on this code
+        if &version > to {
+            return Err(ContractError::MigrationAheadOfVersion {
+                step: step.version.to_string(),
+                version: to.to_string(),
+            });
+        }
+        if &version > from {
+            pending.push(step);
+        }
+    }
+
+    Ok(pending)
+}
+
+/// Brings a deployed Marketplace's storage up to this release. Config carried by the
+/// MigrateMsg is applied by the caller once storage is in the current layout.
+pub fn migrate(mut deps: DepsMut) -> Result<Response, ContractError> {
+    let stored = cw2::get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(cw2::VersionError::WrongContract {
+            expected: CONTRACT_NAME.to_string(),
+            found: stored.contract,
+        }
+        .into());
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if to < from {
+        return Err(ContractError::CannotDowngrade {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+    if from < parse_version(MIN_MIGRATE_VERSION)? {
+        return Err(ContractError::UnsupportedMigration { from: from.to_string() });
+    }
+
+    let steps = pending_steps(&from, &to)?;
+    for step in &steps {
+        (step.migrate)(deps.branch())?;
+    }
+
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let applied: Vec<_> = steps.iter().map(|step| step.version).collect();
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string())
+        .add_attribute("steps", applied.join(",")))
+}
@@ -0,0 +1,68 @@
+use cosmwasm_std::{DepsMut, Order, StdResult, Storage};
+
+use crate::state::{
+    save_collection, save_listing, APPROVED_COLLECTIONS, COLLECTIONS_BY_ADDR, LEGACY_BIDS, LEGACY_LISTINGS, STATE,
+};
+use crate::ContractError;
+
+/// 0.2.0 keyed listings by (collection, token_id) in an indexed map, indexed collections by
+/// contract address and moved ownership from the `state` item to cw_ownable.
+pub fn migrate(deps: DepsMut) -> Result<(), ContractError> {
+    migrate_listings(deps.storage)?;
+    index_collections(deps.storage)?;
+    migrate_owner(deps)?;
+
+    Ok(())
+}
+
+/// Moves listings from the string-keyed `new_listings:` map into the indexed listings map.
+/// Keys are rebuilt from each listing's own collection and token_id, so names containing ':' survive.
+/// 0.1.x accepted bids inside the listing payload without escrow, so they are moved to `LEGACY_BIDS`
+/// for the owner to check instead of riding along into refunds.
+fn migrate_listings(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy = LEGACY_LISTINGS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (key, mut listing) in legacy {
+        for bid in listing.bids.take().unwrap_or_default() {
+            LEGACY_BIDS.save(storage, (&listing.collection, &listing.token_id, &bid.bidder), &bid)?;
+        }
+        save_listing(storage, &listing)?;
+        LEGACY_LISTINGS.remove(storage, &key);
+    }
+
+    Ok(())
+}
+
+/// Fills `COLLECTIONS_BY_ADDR` for the collections approved before it existed.
+/// 0.1.x did not stop an address being approved twice, which would leave its NFTs ambiguous.
+fn index_collections(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let collections = APPROVED_COLLECTIONS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (_, collection) in collections {
+        if let Some(name) = COLLECTIONS_BY_ADDR.may_load(storage, &collection.contract_addr)? {
+            return Err(ContractError::DuplicateCollectionAddress {
+                contract_addr: collection.contract_addr.to_string(),
+                name,
+            });
+        }
+        save_collection(storage, &collection)?;
+    }
+
+    Ok(())
+}
+
+/// Hands the owner recorded in the legacy `state` item over to cw_ownable.
+fn migrate_owner(deps: DepsMut) -> StdResult<()> {
+    if let Some(state) = STATE.may_load(deps.storage)? {
+        if cw_ownable::get_ownership(deps.storage)?.owner.is_none() {
+            cw_ownable::initialize_owner(deps.storage, deps.api, Some(state.owner.as_str()))?;
+        }
+        STATE.remove(deps.storage);
+    }
+
+    Ok(())
+}
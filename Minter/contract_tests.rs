@@ -0,0 +1,735 @@
+#![cfg(test)]
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{coin, coins, from_json, Addr, BankMsg, Coin, CosmosMsg, Empty, OwnedDeps, Response, Uint128};
+
+use crate::entry;
+use crate::msg::{PauseStateResponse, RoleMembersResponse, RoyaltiesInfoResponse, RolesResponse, TokenTraitsResponse};
+use crate::state::{
+    CatalogKind, GloChipDetails, GloNFT, GloNFTType, KeyDetails, KeyType, PauseScope, PerformanceCategoryType,
+    PriceUpdateType, Rarity, Role, Royalty, RoyaltyUpdateType, SeasonEditType, TokenType, GLOCHIPS, HOLOKEYS,
+    MINT_DENOM, RENAMED_IDS, SEASONS,
+};
+use crate::{ContractError, Cw2981QueryMsg, ExecuteMsg, Extension, InstantiateMsg, OwnershipError, QueryMsg};
+
+const OWNER: &str = "owner";
+const MANAGER: &str = "manager";
+const TREASURER: &str = "treasurer";
+const GUARDIAN: &str = "guardian";
+const USER: &str = "user";
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+fn setup_contract() -> MockDeps {
+    let mut deps = mock_dependencies();
+    entry::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            name: "GloChips".into(),
+            symbol: "GLO".into(),
+            minter: OWNER.into(),
+        },
+    )
+    .unwrap();
+    deps
+}
+
+fn execute_with_funds(
+    deps: &mut MockDeps,
+    sender: &str,
+    funds: &[Coin],
+    msg: ExecuteMsg<Extension, Empty>,
+) -> Result<Response, ContractError> {
+    entry::execute(deps.as_mut(), mock_env(), mock_info(sender, funds), msg)
+}
+
+fn execute(deps: &mut MockDeps, sender: &str, msg: ExecuteMsg<Extension, Empty>) -> Result<Response, ContractError> {
+    execute_with_funds(deps, sender, &[], msg)
+}
+
+fn query<R: serde::de::DeserializeOwned>(deps: &MockDeps, msg: QueryMsg<Cw2981QueryMsg>) -> R {
+    from_json(entry::query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+fn grant(deps: &mut MockDeps, role: Role, address: &str) {
+    execute(deps, OWNER, ExecuteMsg::GrantRole { role, address: address.into() }).unwrap();
+}
+
+fn attr(res: &Response, key: &str) -> String {
+    res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+}
+
+fn rarity_name(rarity: &Rarity) -> &'static str {
+    match rarity {
+        Rarity::Generic => "generic",
+        Rarity::Esoteric => "esoteric",
+        Rarity::Spectral => "spectral",
+    }
+}
+
+fn glonft(id: &str, probability: Option<u8>) -> GloNFT {
+    GloNFT {
+        id: id.into(),
+        rarity: Rarity::Generic,
+        uri: format!("ipfs://{id}"),
+        count: None,
+        price: None,
+        probability,
+        royalty: None,
+    }
+}
+
+/// Season "s1" holding a `<rarity>_chip` GloChip, each opening into `<rarity>_nft`, and a `<rarity>_key` HoloKey
+/// for every rarity. Generic keys sell for `key_price`, the others are free.
+fn setup_catalog(deps: &mut MockDeps, key_price: u128) {
+    execute(deps, OWNER, ExecuteMsg::CreateSeason { season_id: "s1".into() }).unwrap();
+    for (rarity, key_type) in [
+        (Rarity::Generic, KeyType::GenericKey),
+        (Rarity::Esoteric, KeyType::EsotericKey),
+        (Rarity::Spectral, KeyType::SpectralKey),
+    ] {
+        let name = rarity_name(&rarity);
+        execute(
+            deps,
+            OWNER,
+            ExecuteMsg::CreateGloChip {
+                id: format!("{name}_chip"),
+                rarity: rarity.clone(),
+                uri: format!("ipfs://{name}_chip"),
+                special: false,
+                price: None,
+                items: vec![glonft(&format!("{name}_nft"), Some(100))],
+                royalty: None,
+            },
+        )
+        .unwrap();
+        execute(
+            deps,
+            OWNER,
+            ExecuteMsg::CreateKey { id: format!("{name}_key"), rarity: rarity.clone(), uri: format!("ipfs://{name}_key") },
+        )
+        .unwrap();
+        execute(
+            deps,
+            OWNER,
+            ExecuteMsg::AddGloChipToSeason { season_id: "s1".into(), glochip_id: format!("{name}_chip"), rarity },
+        )
+        .unwrap();
+        execute(
+            deps,
+            OWNER,
+            ExecuteMsg::AddKeyToSeason { season_id: "s1".into(), key_id: format!("{name}_key"), key_type },
+        )
+        .unwrap();
+    }
+    execute(
+        deps,
+        OWNER,
+        ExecuteMsg::UpdatePrices {
+            price_update_type: PriceUpdateType::HoloKeyPrices {
+                generic_price: Some(key_price),
+                esoteric_price: Some(0),
+                spectral_price: Some(0),
+            },
+        },
+    )
+    .unwrap();
+}
+
+/// Mints a free reward GloChip of the season to USER, returning its token id and rarity
+fn mint_reward_chip(deps: &mut MockDeps) -> Result<(String, Rarity), ContractError> {
+    let res = execute(
+        deps,
+        OWNER,
+        ExecuteMsg::Mint {
+            owner: USER.into(),
+            extension: None,
+            token_type: TokenType::GloChip(GloChipDetails {
+                special: false,
+                glochip_id: None,
+                performance_category: Some(PerformanceCategoryType::Tier1),
+                season_id: Some("s1".into()),
+            }),
+        },
+    )?;
+    let token_id = attr(&res, "token_id");
+    let traits: TokenTraitsResponse = query(deps, QueryMsg::TokenTraits { token_id: token_id.clone() });
+    Ok((token_id, traits.rarity.unwrap()))
+}
+
+fn mint_key(deps: &mut MockDeps, key_id: &str, funds: &[Coin]) -> Result<String, ContractError> {
+    let res = execute_with_funds(
+        deps,
+        USER,
+        funds,
+        ExecuteMsg::Mint {
+            owner: USER.into(),
+            extension: None,
+            token_type: TokenType::Key(KeyDetails { key_id: key_id.into(), season_id: "s1".into() }),
+        },
+    )?;
+    Ok(attr(&res, "token_id"))
+}
+
+fn open(deps: &mut MockDeps, glochip_id: &str, key_id: &str) -> Result<String, ContractError> {
+    let res = execute(
+        deps,
+        USER,
+        ExecuteMsg::Mint {
+            owner: USER.into(),
+            extension: None,
+            token_type: TokenType::GloNFT(GloNFTType::Opening { glochip_id: glochip_id.into(), key_id: key_id.into() }),
+        },
+    )?;
+    Ok(attr(&res, "token_id"))
+}
+
+fn royalty(address: &str, percentage: u64) -> Option<Royalty> {
+    Some(Royalty {
+        payment_address: Addr::unchecked(address),
+        percentage,
+    })
+}
+
+#[test]
+fn owner_grants_and_revokes_roles() {
+    let mut deps = setup_contract();
+
+    // without the role, catalog actions are refused
+    let err = execute(&mut deps, MANAGER, ExecuteMsg::CreateSeason { season_id: "s1".into() }).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "catalog_manager".into() });
+
+    grant(&mut deps, Role::CatalogManager, MANAGER);
+    let res: RolesResponse = query(&deps, QueryMsg::Roles { address: MANAGER.into() });
+    assert_eq!(res.roles, vec![Role::CatalogManager]);
+    let res: RoleMembersResponse = query(
+        &deps,
+        QueryMsg::RoleMembers { role: Role::CatalogManager, start_after: None, limit: None },
+    );
+    assert_eq!(res.members, vec![Addr::unchecked(MANAGER)]);
+
+    execute(&mut deps, MANAGER, ExecuteMsg::CreateSeason { season_id: "s1".into() }).unwrap();
+
+    execute(&mut deps, OWNER, ExecuteMsg::RevokeRole { role: Role::CatalogManager, address: MANAGER.into() }).unwrap();
+    let res: RolesResponse = query(&deps, QueryMsg::Roles { address: MANAGER.into() });
+    assert!(res.roles.is_empty());
+
+    let err = execute(&mut deps, MANAGER, ExecuteMsg::CreateSeason { season_id: "s2".into() }).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "catalog_manager".into() });
+}
+
+#[test]
+fn only_owner_manages_roles() {
+    let mut deps = setup_contract();
+    grant(&mut deps, Role::CatalogManager, MANAGER);
+
+    // role holders cannot hand out roles, not even their own
+    let err = execute(&mut deps, MANAGER, ExecuteMsg::GrantRole { role: Role::CatalogManager, address: USER.into() }).unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    let err = execute(&mut deps, MANAGER, ExecuteMsg::RevokeRole { role: Role::CatalogManager, address: MANAGER.into() }).unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    // revoking a role that was never granted is reported
+    let err = execute(&mut deps, OWNER, ExecuteMsg::RevokeRole { role: Role::Treasurer, address: MANAGER.into() }).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "treasurer".into() });
+}
+
+#[test]
+fn roles_only_unlock_their_own_actions() {
+    let mut deps = setup_contract();
+    grant(&mut deps, Role::PriceManager, USER);
+    grant(&mut deps, Role::CatalogManager, MANAGER);
+
+    // royalties belong to the catalog manager, not the price manager
+    let update_royalties = ExecuteMsg::UpdateRoyalties {
+        royalty_update_type: RoyaltyUpdateType::Collection { royalty: royalty("artist", 5) },
+    };
+    let err = execute(&mut deps, USER, update_royalties.clone()).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "catalog_manager".into() });
+    execute(&mut deps, MANAGER, update_royalties.clone()).unwrap();
+    execute(&mut deps, OWNER, update_royalties).unwrap();
+
+    // and prices to the price manager
+    let update_prices = ExecuteMsg::UpdatePrices {
+        price_update_type: PriceUpdateType::HoloKeyPrices {
+            generic_price: Some(10),
+            esoteric_price: None,
+            spectral_price: None,
+        },
+    };
+    let err = execute(&mut deps, MANAGER, update_prices.clone()).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "price_manager".into() });
+    execute(&mut deps, USER, update_prices).unwrap();
+}
+
+#[test]
+fn treasurer_withdraws_any_denom() {
+    let mut deps = setup_contract();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, vec![coin(100, "uluna"), coin(40, "uusd")]);
+
+    let withdraw_all = ExecuteMsg::Withdraw { denom: None, amount: None, recipient: None };
+    let err = execute(&mut deps, USER, withdraw_all.clone()).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "treasurer".into() });
+
+    grant(&mut deps, Role::Treasurer, TREASURER);
+
+    // defaults to the whole mint denom balance, sent to the treasurer
+    let res = execute(&mut deps, TREASURER, withdraw_all).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send { to_address: TREASURER.into(), amount: coins(100, "uluna") }),
+    );
+
+    // any other denom held by the contract can be pulled out too
+    let res = execute(
+        &mut deps,
+        TREASURER,
+        ExecuteMsg::Withdraw {
+            denom: Some("uusd".into()),
+            amount: Some(Uint128::new(15)),
+            recipient: Some(USER.into()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send { to_address: USER.into(), amount: coins(15, "uusd") }),
+    );
+
+    let err = execute(
+        &mut deps,
+        TREASURER,
+        ExecuteMsg::Withdraw { denom: Some("uusd".into()), amount: Some(Uint128::new(41)), recipient: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+    let err = execute(
+        &mut deps,
+        TREASURER,
+        ExecuteMsg::Withdraw { denom: Some("uatom".into()), amount: None, recipient: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
+
+#[test]
+fn guardian_pauses_but_only_owner_unpauses() {
+    let mut deps = setup_contract();
+
+    let pause = ExecuteMsg::Pause { scopes: vec![PauseScope::Transfers] };
+    let err = execute(&mut deps, GUARDIAN, pause.clone()).unwrap_err();
+    assert_eq!(err, ContractError::RoleNotHeld { role: "guardian".into() });
+
+    grant(&mut deps, Role::Guardian, GUARDIAN);
+    execute(&mut deps, GUARDIAN, pause).unwrap();
+    let res: PauseStateResponse = query(&deps, QueryMsg::PauseState {});
+    assert!(res.paused.transfers);
+    assert!(!res.paused.paid_mints && !res.paused.openings);
+
+    let unpause = ExecuteMsg::Unpause { scopes: vec![PauseScope::Transfers] };
+    let err = execute(&mut deps, GUARDIAN, unpause.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    execute(&mut deps, OWNER, unpause).unwrap();
+    let res: PauseStateResponse = query(&deps, QueryMsg::PauseState {});
+    assert!(!res.paused.transfers);
+
+    let err = execute(&mut deps, GUARDIAN, ExecuteMsg::Pause { scopes: vec![] }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPauseScopes {});
+}
+
+#[test]
+fn paused_paid_mints_keep_free_mints_open() {
+    let mut deps = setup_contract();
+    setup_catalog(&mut deps, 25);
+    execute(&mut deps, OWNER, ExecuteMsg::Pause { scopes: vec![PauseScope::PaidMints] }).unwrap();
+
+    let err = mint_key(&mut deps, "generic_key", &coins(25, MINT_DENOM)).unwrap_err();
+    assert_eq!(err, ContractError::Paused { scope: "paid_mints".into() });
+
+    // reward GloChips and free keys are still handed out
+    mint_reward_chip(&mut deps).unwrap();
+    mint_key(&mut deps, "spectral_key", &[]).unwrap();
+
+    execute(&mut deps, OWNER, ExecuteMsg::Unpause { scopes: vec![PauseScope::PaidMints] }).unwrap();
+    mint_key(&mut deps, "generic_key", &coins(25, MINT_DENOM)).unwrap();
+}
+
+#[test]
+fn paused_openings_and_transfers() {
+    let mut deps = setup_contract();
+    setup_catalog(&mut deps, 0);
+    let (chip, rarity) = mint_reward_chip(&mut deps).unwrap();
+    let key = mint_key(&mut deps, &format!("{}_key", rarity_name(&rarity)), &[]).unwrap();
+
+    execute(&mut deps, OWNER, ExecuteMsg::Pause { scopes: vec![PauseScope::Openings, PauseScope::Transfers] }).unwrap();
+
+    let err = open(&mut deps, &chip, &key).unwrap_err();
+    assert_eq!(err, ContractError::Paused { scope: "openings".into() });
+    let err = execute(&mut deps, USER, ExecuteMsg::TransferNft { recipient: OWNER.into(), token_id: chip.clone() }).unwrap_err();
+    assert_eq!(err, ContractError::Paused { scope: "transfers".into() });
+    let err = execute(
+        &mut deps,
+        USER,
+        ExecuteMsg::SendNft { contract: OWNER.into(), token_id: chip.clone(), msg: Default::default() },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Paused { scope: "transfers".into() });
+
+    // lifting openings alone leaves transfers halted
+    execute(&mut deps, OWNER, ExecuteMsg::Unpause { scopes: vec![PauseScope::Openings] }).unwrap();
+    let glonft = open(&mut deps, &chip, &key).unwrap();
+    let err = execute(&mut deps, USER, ExecuteMsg::TransferNft { recipient: OWNER.into(), token_id: glonft.clone() }).unwrap_err();
+    assert_eq!(err, ContractError::Paused { scope: "transfers".into() });
+
+    execute(&mut deps, OWNER, ExecuteMsg::Unpause { scopes: vec![PauseScope::Transfers] }).unwrap();
+    execute(&mut deps, USER, ExecuteMsg::TransferNft { recipient: OWNER.into(), token_id: glonft }).unwrap();
+}
+
+fn rename_glochip(deps: &mut MockDeps, glochip_id: &str, new_id: &str) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        OWNER,
+        ExecuteMsg::EditGloChip { glochip_id: glochip_id.into(), new_id: Some(new_id.into()), new_uri: None, new_items: None },
+    )
+}
+
+fn rename_key(deps: &mut MockDeps, key_id: &str, new_id: &str) -> Result<Response, ContractError> {
+    execute(deps, OWNER, ExecuteMsg::EditKey { key_id: key_id.into(), new_id: Some(new_id.into()), new_uri: None })
+}
+
+#[test]
+fn rename_rejects_live_and_retired_ids() {
+    let mut deps = setup_contract();
+    setup_catalog(&mut deps, 0);
+
+    // another live GloChip
+    let err = rename_glochip(&mut deps, "generic_chip", "esoteric_chip").unwrap_err();
+    assert_eq!(err, ContractError::IdAlreadyExists { id: "esoteric_chip".into() });
+    let err = rename_glochip(&mut deps, "generic_chip", "").unwrap_err();
+    assert_eq!(err, ContractError::InvalidTokenId { token_id: "".into() });
+
+    // an id retired by another GloChip's rename, through an edit or a create
+    rename_glochip(&mut deps, "generic_chip", "generic_chip_v2").unwrap();
+    let err = rename_glochip(&mut deps, "esoteric_chip", "generic_chip").unwrap_err();
+    assert_eq!(err, ContractError::IdAlreadyExists { id: "generic_chip".into() });
+    let err = execute(
+        &mut deps,
+        OWNER,
+        ExecuteMsg::CreateGloChip {
+            id: "generic_chip".into(),
+            rarity: Rarity::Generic,
+            uri: "ipfs://generic_chip".into(),
+            special: false,
+            price: None,
+            items: vec![glonft("generic_nft", Some(100))],
+            royalty: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IdAlreadyExists { id: "generic_chip".into() });
+
+    // the same goes for keys
+    rename_key(&mut deps, "generic_key", "generic_key_v2").unwrap();
+    let err = execute(
+        &mut deps,
+        OWNER,
+        ExecuteMsg::CreateKey { id: "generic_key".into(), rarity: Rarity::Generic, uri: "ipfs://generic_key".into() },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IdAlreadyExists { id: "generic_key".into() });
+
+    // a GloChip may take back its own former id
+    rename_glochip(&mut deps, "generic_chip_v2", "generic_chip").unwrap();
+    assert!(GLOCHIPS.has(deps.as_ref().storage, "generic_chip"));
+    assert_eq!(
+        RENAMED_IDS.load(deps.as_ref().storage, (CatalogKind::GloChip.as_str(), "generic_chip_v2")).unwrap(),
+        "generic_chip",
+    );
+    assert!(!RENAMED_IDS.has(deps.as_ref().storage, (CatalogKind::GloChip.as_str(), "generic_chip")));
+}
+
+#[test]
+fn renamed_editions_open_and_resolve_to_the_latest_id() {
+    let mut deps = setup_contract();
+    setup_catalog(&mut deps, 0);
+    let (chip, rarity) = mint_reward_chip(&mut deps).unwrap();
+    let name = rarity_name(&rarity);
+    let key = mint_key(&mut deps, &format!("{name}_key"), &[]).unwrap();
+
+    // A -> B -> C, for both the GloChip and the HoloKey
+    rename_glochip(&mut deps, &format!("{name}_chip"), "chip_b").unwrap();
+    rename_glochip(&mut deps, "chip_b", "chip_c").unwrap();
+    rename_key(&mut deps, &format!("{name}_key"), "key_b").unwrap();
+    rename_key(&mut deps, "key_b", "key_c").unwrap();
+
+    let season = SEASONS.load(deps.as_ref().storage, "s1").unwrap();
+    let (season_chip, season_key) = match rarity {
+        Rarity::Generic => (season.generic_glochip, season.generic_key),
+        Rarity::Esoteric => (season.esoteric_glochip, season.esoteric_key),
+        Rarity::Spectral => (season.spectral_glochip, season.spectral_key),
+    };
+    assert_eq!(season_chip.as_deref(), Some("chip_c"));
+    assert_eq!(season_key.as_deref(), Some("key_c"));
+
+    let traits: TokenTraitsResponse = query(&deps, QueryMsg::TokenTraits { token_id: chip.clone() });
+    assert_eq!(traits.base_id, "chip_c");
+    assert_eq!(traits.season_id.as_deref(), Some("s1"));
+    let traits: TokenTraitsResponse = query(&deps, QueryMsg::TokenTraits { token_id: key.clone() });
+    assert_eq!(traits.base_id, "key_c");
+    assert_eq!(traits.rarity, Some(rarity.clone()));
+
+    // editions minted under the first ids still open, and the count carries on
+    let glonft_id = open(&mut deps, &chip, &key).unwrap();
+    assert_eq!(glonft_id, format!("{name}_nft_1"));
+    let traits: TokenTraitsResponse = query(&deps, QueryMsg::TokenTraits { token_id: glonft_id });
+    assert_eq!(traits.season_id.as_deref(), Some("s1"));
+
+    let next_key = mint_key(&mut deps, "key_c", &[]).unwrap();
+    assert_eq!(next_key, "key_c_2");
+}
+
+#[test]
+fn rename_season_moves_slots_and_back_references() {
+    let mut deps = setup_contract();
+    setup_catalog(&mut deps, 0);
+    execute(&mut deps, OWNER, ExecuteMsg::CreateSeason { season_id: "s2".into() }).unwrap();
+
+    let rename = |id: &str| ExecuteMsg::EditSeason {
+        season_id: "s1".into(),
+        edit_type: SeasonEditType::Rename(id.into()),
+    };
+    let err = execute(&mut deps, OWNER, rename("s2")).unwrap_err();
+    assert_eq!(err, ContractError::SeasonAlreadyExists {});
+    let err = execute(&mut deps, OWNER, rename("")).unwrap_err();
+    assert_eq!(err, ContractError::InvalidTokenId { token_id: "".into() });
+
+    execute(&mut deps, OWNER, rename("spring")).unwrap();
+    assert!(!SEASONS.has(deps.as_ref().storage, "s1"));
+    let season = SEASONS.load(deps.as_ref().storage, "spring").unwrap();
+    assert_eq!(season.id, "spring");
+    assert_eq!(season.generic_glochip.as_deref(), Some("generic_chip"));
+    assert_eq!(season.spectral_key.as_deref(), Some("spectral_key"));
+
+    for name in ["generic", "esoteric", "spectral"] {
+        let glochip = GLOCHIPS.load(deps.as_ref().storage, &format!("{name}_chip")).unwrap();
+        assert_eq!(glochip.season_id.as_deref(), Some("spring"));
+        let key = HOLOKEYS.load(deps.as_ref().storage, &format!("{name}_key")).unwrap();
+        assert_eq!(key.season_id.as_deref(), Some("spring"));
+    }
+
+    // the renamed season keeps minting and opening
+    let res = execute(
+        &mut deps,
+        OWNER,
+        ExecuteMsg::Mint {
+            owner: USER.into(),
+            extension: None,
+            token_type: TokenType::GloChip(GloChipDetails {
+                special: false,
+                glochip_id: None,
+                performance_category: Some(PerformanceCategoryType::Tier1),
+                season_id: Some("spring".into()),
+            }),
+        },
+    )
+    .unwrap();
+    let chip = attr(&res, "token_id");
+    let traits: TokenTraitsResponse = query(&deps, QueryMsg::TokenTraits { token_id: chip.clone() });
+    assert_eq!(traits.season_id.as_deref(), Some("spring"));
+    let key = mint_key(&mut deps, &format!("{}_key", rarity_name(&traits.rarity.unwrap())), &[]).unwrap();
+    open(&mut deps, &chip, &key).unwrap();
+}
+
+#[test]
+fn items_taken_out_of_a_season_slot_can_join_another_season() {
+    let mut deps = setup_contract();
+    setup_catalog(&mut deps, 0);
+    execute(&mut deps, OWNER, ExecuteMsg::CreateSeason { season_id: "s2".into() }).unwrap();
+    let edit = |season_id: &str, edit_type: SeasonEditType| ExecuteMsg::EditSeason { season_id: season_id.into(), edit_type };
+
+    // a cleared slot releases its HoloKey
+    execute(&mut deps, OWNER, edit("s1", SeasonEditType::Key { rarity: Rarity::Spectral, key_id: None })).unwrap();
+    assert_eq!(HOLOKEYS.load(deps.as_ref().storage, "spectral_key").unwrap().season_id, None);
+    execute(&mut deps, OWNER, edit("s2", SeasonEditType::Key { rarity: Rarity::Spectral, key_id: Some("spectral_key".into()) }))
+        .unwrap();
+    assert_eq!(HOLOKEYS.load(deps.as_ref().storage, "spectral_key").unwrap().season_id.as_deref(), Some("s2"));
+
+    // a replaced slot releases its GloChip
+    execute(
+        &mut deps,
+        OWNER,
+        ExecuteMsg::CreateGloChip {
+            id: "generic_chip_2".into(),
+            rarity: Rarity::Generic,
+            uri: "ipfs://generic_chip_2".into(),
+            special: false,
+            price: None,
+            items: vec![glonft("generic_nft_2", Some(100))],
+            royalty: None,
+        },
+    )
+    .unwrap();
+    execute(
+        &mut deps,
+        OWNER,
+        edit("s1", SeasonEditType::GloChip { rarity: Rarity::Generic, glochip_id: Some("generic_chip_2".into()) }),
+    )
+    .unwrap();
+    assert_eq!(GLOCHIPS.load(deps.as_ref().storage, "generic_chip").unwrap().season_id, None);
+    execute(
+        &mut deps,
+        OWNER,
+        edit("s2", SeasonEditType::GloChip { rarity: Rarity::Generic, glochip_id: Some("generic_chip".into()) }),
+    )
+    .unwrap();
+
+    let s1 = SEASONS.load(deps.as_ref().storage, "s1").unwrap();
+    assert_eq!(s1.generic_glochip.as_deref(), Some("generic_chip_2"));
+    assert_eq!(s1.spectral_key, None);
+    let s2 = SEASONS.load(deps.as_ref().storage, "s2").unwrap();
+    assert_eq!(s2.generic_glochip.as_deref(), Some("generic_chip"));
+    assert_eq!(s2.spectral_key.as_deref(), Some("spectral_key"));
+
+    // re-setting a slot to what it already holds keeps the assignment
+    execute(&mut deps, OWNER, edit("s2", SeasonEditType::Key { rarity: Rarity::Spectral, key_id: Some("spectral_key".into()) }))
+        .unwrap();
+    assert_eq!(HOLOKEYS.load(deps.as_ref().storage, "spectral_key").unwrap().season_id.as_deref(), Some("s2"));
+
+    // an item still in a slot stays bound to its season
+    let err = execute(&mut deps, OWNER, edit("s2", SeasonEditType::Key { rarity: Rarity::Generic, key_id: Some("generic_key".into()) }))
+        .unwrap_err();
+    assert_eq!(err, ContractError::KeyAlreadyAssigned {});
+}
+
+#[test]
+fn deleting_a_renamed_special_glonft_keeps_its_former_ids_retired() {
+    let mut deps = setup_contract();
+    execute(&mut deps, OWNER, ExecuteMsg::CreateSpecialGloNft { glonfts: vec![glonft("relic", None)] }).unwrap();
+    for (from, to) in [("relic", "relic_b"), ("relic_b", "relic_c")] {
+        execute(
+            &mut deps,
+            OWNER,
+            ExecuteMsg::EditSpecialGloNft { glonft_id: from.into(), new_id: Some(to.into()), new_uri: None },
+        )
+        .unwrap();
+    }
+
+    execute(&mut deps, OWNER, ExecuteMsg::DeleteSpecialGloNft { glonfts: vec!["relic_c".into()] }).unwrap();
+    let kind = CatalogKind::SpecialGloNft.as_str();
+    assert_eq!(RENAMED_IDS.load(deps.as_ref().storage, (kind, "relic")).unwrap(), "relic_c");
+    assert_eq!(RENAMED_IDS.load(deps.as_ref().storage, (kind, "relic_b")).unwrap(), "relic_c");
+
+    // editions minted under any of its ids still resolve to it, so none of them can be reused
+    for id in ["relic", "relic_b", "relic_c"] {
+        let err = execute(&mut deps, OWNER, ExecuteMsg::CreateSpecialGloNft { glonfts: vec![glonft(id, None)] }).unwrap_err();
+        assert_eq!(err, ContractError::IdAlreadyExists { id: id.into() });
+    }
+
+    // nor can another entry be renamed onto them
+    execute(&mut deps, OWNER, ExecuteMsg::CreateSpecialGloNft { glonfts: vec![glonft("idol", None)] }).unwrap();
+    let err = execute(
+        &mut deps,
+        OWNER,
+        ExecuteMsg::EditSpecialGloNft { glonft_id: "idol".into(), new_id: Some("relic_c".into()), new_uri: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IdAlreadyExists { id: "relic_c".into() });
+}
+
+fn royalty_info(deps: &MockDeps, token_id: &str) -> (String, u128) {
+    let res: RoyaltiesInfoResponse = query(
+        deps,
+        QueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltyInfo { token_id: token_id.into(), sale_price: Uint128::new(1_000) },
+        },
+    );
+    (res.address, res.royalty_amount.u128())
+}
+
+fn set_royalty(deps: &mut MockDeps, royalty_update_type: RoyaltyUpdateType) -> Result<Response, ContractError> {
+    execute(deps, OWNER, ExecuteMsg::UpdateRoyalties { royalty_update_type })
+}
+
+#[test]
+fn royalties_fall_back_from_glonft_to_glochip_to_collection() {
+    let mut deps = setup_contract();
+    execute(
+        &mut deps,
+        OWNER,
+        ExecuteMsg::CreateGloChip {
+            id: "chip".into(),
+            rarity: Rarity::Spectral,
+            uri: "ipfs://chip".into(),
+            special: true,
+            price: Some(0),
+            items: vec![glonft("nft", Some(100))],
+            royalty: None,
+        },
+    )
+    .unwrap();
+    let mut relic = glonft("relic", None);
+    relic.price = Some(0);
+    execute(&mut deps, OWNER, ExecuteMsg::CreateSpecialGloNft { glonfts: vec![relic] }).unwrap();
+
+    let special_chip = TokenType::GloChip(GloChipDetails {
+        special: true,
+        glochip_id: Some("chip".into()),
+        performance_category: None,
+        season_id: None,
+    });
+    for _ in 0..2 {
+        execute(&mut deps, USER, ExecuteMsg::Mint { owner: USER.into(), extension: None, token_type: special_chip.clone() }).unwrap();
+    }
+    let special_nft = TokenType::GloNFT(GloNFTType::Special { item_id: "relic".into() });
+    execute(&mut deps, USER, ExecuteMsg::Mint { owner: USER.into(), extension: None, token_type: special_nft }).unwrap();
+    let opening = TokenType::GloNFT(GloNFTType::SpecialOpening { special_glochip_id: "chip_1".into() });
+    execute(&mut deps, USER, ExecuteMsg::Mint { owner: USER.into(), extension: None, token_type: opening }).unwrap();
+
+    // nothing set yet
+    assert_eq!(royalty_info(&deps, "chip_2"), (String::new(), 0));
+    assert_eq!(royalty_info(&deps, "nft_1"), (String::new(), 0));
+
+    // the collection royalty covers every token
+    set_royalty(&mut deps, RoyaltyUpdateType::Collection { royalty: royalty("collection", 2) }).unwrap();
+    assert_eq!(royalty_info(&deps, "chip_2"), ("collection".into(), 20));
+    assert_eq!(royalty_info(&deps, "nft_1"), ("collection".into(), 20));
+    assert_eq!(royalty_info(&deps, "relic_1"), ("collection".into(), 20));
+
+    // a GloChip royalty overrides it for the GloChip and the GloNFTs it opens into
+    set_royalty(&mut deps, RoyaltyUpdateType::GloChip { id: "chip".into(), royalty: royalty("chip_artist", 5) }).unwrap();
+    assert_eq!(royalty_info(&deps, "chip_2"), ("chip_artist".into(), 50));
+    assert_eq!(royalty_info(&deps, "nft_1"), ("chip_artist".into(), 50));
+
+    // and a GloNFT royalty overrides both
+    set_royalty(
+        &mut deps,
+        RoyaltyUpdateType::GloNFT { glochip_id: Some("chip".into()), id: "nft".into(), royalty: royalty("nft_artist", 10) },
+    )
+    .unwrap();
+    set_royalty(
+        &mut deps,
+        RoyaltyUpdateType::GloNFT { glochip_id: None, id: "relic".into(), royalty: royalty("relic_artist", 7) },
+    )
+    .unwrap();
+    assert_eq!(royalty_info(&deps, "nft_1"), ("nft_artist".into(), 100));
+    assert_eq!(royalty_info(&deps, "chip_2"), ("chip_artist".into(), 50));
+    assert_eq!(royalty_info(&deps, "relic_1"), ("relic_artist".into(), 70));
+
+    // clearing an override falls back to the next level
+    set_royalty(&mut deps, RoyaltyUpdateType::GloChip { id: "chip".into(), royalty: None }).unwrap();
+    assert_eq!(royalty_info(&deps, "chip_2"), ("collection".into(), 20));
+    assert_eq!(royalty_info(&deps, "nft_1"), ("nft_artist".into(), 100));
+
+    let err = set_royalty(&mut deps, RoyaltyUpdateType::Collection { royalty: royalty("collection", 101) }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidRoyaltyPercentage {});
+    let err = set_royalty(
+        &mut deps,
+        RoyaltyUpdateType::GloNFT { glochip_id: Some("chip".into()), id: "missing".into(), royalty: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::GloNFTNotFound {});
+}
@@ -99,9 +99,36 @@ pub enum ContractError {
     #[error("Unable to find GloNFT")]
     GloNFTNotFound {},
 
+    #[error("Royalty percentage must be between 0 and 100")]
+    InvalidRoyaltyPercentage {},
+
     #[error("Invalid Item Count")]
     InvalidItemCount {},
 
+    #[error("Address does not hold the {role} role")]
+    RoleNotHeld { role: String },
+
+    #[error("No pause scopes given")]
+    InvalidPauseScopes {},
+
+    #[error("Minter {scope} are paused")]
+    Paused { scope: String },
+
+    #[error("Id {id} is already in use")]
+    IdAlreadyExists { id: String },
+
+    #[error("Invalid version: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("Cannot migrate from {from} down to {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Migration from version {from} is not supported")]
+    UnsupportedMigration { from: String },
+
+    #[error("Migration step {step} is newer than contract version {version}")]
+    MigrationAheadOfVersion { step: String, version: String },
+
     #[error("Approval not found for: {spender}")]
     ApprovalNotFound { spender: String },
 }
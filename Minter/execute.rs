@@ -2,7 +2,7 @@ use cw_ownable::OwnershipError;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{Binary, CustomMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use cosmwasm_std::{Addr, Api, BankMsg, Binary, Coin, CustomMsg, Deps, DepsMut, Empty, Env, Event, MessageInfo, Response, StdResult, Storage, Uint128};
 
 use cw721::{ContractInfoResponse, Cw721Execute, Cw721ReceiveMsg, Expiration};
 
@@ -11,7 +11,8 @@ use crate::msg::{ExecuteMsg, InstantiateMsg};
 use crate::state::{ 
     Approval, Cw721Contract, TokenInfo, TokenType, KeyType, GloChip, GLOCHIPS, Rarity, RewardProbabilities,
     Season, SEASONS, HoloKey, HOLOKEYS, SeasonEditType, GloChipDetails, KeyDetails, GloNFTType, SPECIAL_GLO_NFTS, 
-    GloNFT, PriceUpdateType, HOLOKEY_PRICING, HoloKeyPricing 
+    GloNFT, PriceUpdateType, HOLOKEY_PRICING, HoloKeyPricing, Royalty, RoyaltyUpdateType, COLLECTION_ROYALTY,
+    PauseScope, PAUSE_STATE, Role, ROLES, CatalogKind, RENAMED_IDS, resolve_id, MINT_DENOM
 };
 use sha2::{Sha256, Digest};
 
@@ -82,8 +83,8 @@ where
             ExecuteMsg::CreateSeason { season_id } => {
                 self.create_season(deps, info, season_id)
             },
-            ExecuteMsg::CreateGloChip { id, rarity, uri, special, price, items} => { 
-                self.create_glochip(deps, info, rarity, id, uri, special, price, items)
+            ExecuteMsg::CreateGloChip { id, rarity, uri, special, price, items, royalty } => { 
+                self.create_glochip(deps, info, rarity, id, uri, special, price, items, royalty)
             },
             ExecuteMsg::CreateKey { id, rarity, uri } => { 
                 self.create_key(deps, info, rarity, id, uri)
@@ -96,6 +97,7 @@ where
                 edit_type,
             } => self.edit_season(deps, info, season_id, edit_type),
             ExecuteMsg::UpdatePrices { price_update_type } => self.update_prices(deps, info, price_update_type),
+            ExecuteMsg::UpdateRoyalties { royalty_update_type } => self.update_royalties(deps, info, royalty_update_type),
             ExecuteMsg::AddGloChipToSeason { season_id, glochip_id, rarity } => {
                 self.add_glochip_to_season(deps, info, season_id, glochip_id, rarity)
             },
@@ -112,6 +114,11 @@ where
                 self.edit_special_glonft(deps, info, glonft_id, new_id, new_uri)
             },
             ExecuteMsg::DeleteSpecialGloNft { glonfts } => self.delete_special_glonfts(deps, info, glonfts),
+            ExecuteMsg::Pause { scopes } => self.update_pause(deps, info, scopes, true),
+            ExecuteMsg::Unpause { scopes } => self.update_pause(deps, info, scopes, false),
+            ExecuteMsg::GrantRole { role, address } => self.grant_role(deps, info, role, address),
+            ExecuteMsg::RevokeRole { role, address } => self.revoke_role(deps, info, role, address),
+            ExecuteMsg::Withdraw { denom, amount, recipient } => self.withdraw(deps, env, info, denom, amount, recipient),
             ExecuteMsg::Extension { msg: _ } => Ok(Response::default()),
         }
     }
@@ -137,13 +144,21 @@ where
         // Retrieve the required price for the token
         let required_price = self.get_price(deps.as_ref(), token_type.clone())?;
 
+        // Free reward mints stay open while paid mints are paused
+        if required_price > 0 {
+            assert_not_paused(deps.storage, PauseScope::PaidMints)?;
+        }
+        if let TokenType::GloNFT(GloNFTType::Opening { .. } | GloNFTType::SpecialOpening { .. }) = &token_type {
+            assert_not_paused(deps.storage, PauseScope::Openings)?;
+        }
+
         // Check if the attached funds match the required price
         if required_price > 0 {
             // Perform funds check only if the price is greater than zero
             let provided_funds = info
                 .funds
                 .iter()
-                .find(|coin| coin.denom == "uluna")
+                .find(|coin| coin.denom == MINT_DENOM)
                 .map(|coin| coin.amount.u128())
                 .unwrap_or(0);
     
@@ -240,7 +255,7 @@ where
         info: &MessageInfo,
         details: &GloChipDetails,
     ) -> Result<(String, String), ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::RewardDistributor)?;
 
         // Determine reward probabilities for the given performance_category
         let reward_probabilities = match &details.performance_category {
@@ -345,6 +360,10 @@ where
         let base_key_id = key_id.rsplitn(2, '_').last()
             .ok_or(ContractError::InvalidTokenId { token_id: key_id.to_string() })?;
     
+        // Editions minted before a rename still carry the old ids
+        let base_glochip_id = resolve_id(deps.storage, CatalogKind::GloChip, base_glochip_id)?;
+        let base_key_id = resolve_id(deps.storage, CatalogKind::HoloKey, base_key_id)?;
+
         // Load the GloChip configuration using the base GloChip ID
         let mut glochip_config = GLOCHIPS.load(deps.storage, &base_glochip_id)?;
 
@@ -362,7 +381,7 @@ where
             Rarity::Spectral => season.spectral_key,
         }.ok_or(ContractError::InvalidKey {})?;
     
-        if Some(base_key_id.to_string()) != Some(expected_key_id) {
+        if base_key_id != expected_key_id {
             return Err(ContractError::InvalidKey {});
         }
     
@@ -380,7 +399,7 @@ where
             let token_uri = glonft.uri.clone();
 
             // Save the updated GloChip
-            GLOCHIPS.save(deps.storage, base_glochip_id, &glochip_config)?;
+            GLOCHIPS.save(deps.storage, &base_glochip_id, &glochip_config)?;
 
             // Burn the GloChip and HoloKey NFTs
             self.opening_burn(deps, vec![glochip_id.to_string(), key_id.to_string()])?;
@@ -425,8 +444,10 @@ where
         let base_glochip_id = glochip_id.rsplitn(2, '_').last()
             .ok_or(ContractError::InvalidTokenId { token_id: glochip_id.to_string() })?;
     
+        let base_glochip_id = resolve_id(deps.storage, CatalogKind::GloChip, base_glochip_id)?;
+
         // Load the GloChip configuration
-        let mut glochip_config = GLOCHIPS.load(deps.storage, base_glochip_id)?;
+        let mut glochip_config = GLOCHIPS.load(deps.storage, &base_glochip_id)?;
     
         // Ensure the GloChip is special
         if !glochip_config.special {
@@ -446,7 +467,7 @@ where
             let token_uri = glonft.uri.clone();
 
             // Save the updated GloChip
-            GLOCHIPS.save(deps.storage, base_glochip_id, &glochip_config)?;
+            GLOCHIPS.save(deps.storage, &base_glochip_id, &glochip_config)?;
 
             // Burn the GloChip NFT
             self.opening_burn(deps, vec![glochip_id.to_string()])?;
@@ -523,7 +544,7 @@ where
         info: MessageInfo,
         season_id: String,
     ) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         // Check if the season already exists to avoid overwriting it
         if SEASONS.has(deps.storage, &season_id) {
@@ -557,9 +578,16 @@ where
         special: bool,
         price: Option<u128>,
         items: Vec<GloNFT>,
+        royalty: Option<Royalty>,
     ) -> Result<Response<C>, ContractError> {
-        // Verify Sender is Owner
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // Verify Sender is a catalog manager or the owner
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
+
+        // Validate the GloChip royalty and any GloNFT overrides
+        validate_royalty(deps.api, &royalty)?;
+        for item in &items {
+            validate_royalty(deps.api, &item.royalty)?;
+        }
 
         // Clone the items vector before transforming it
         let cloned_items = items.clone();
@@ -586,6 +614,7 @@ where
         if total_probability != 100 {
             return Err(ContractError::InvalidProbability {});
         }
+        ensure_not_retired(deps.storage, CatalogKind::GloChip, &id)?;
     
         let new_glochip = GloChip {
             rarity,
@@ -596,6 +625,7 @@ where
             count: 0,
             price,
             season_id: None,
+            royalty,
         };
     
         GLOCHIPS.save(deps.storage, new_glochip.id.as_str(), &new_glochip)?;
@@ -611,8 +641,9 @@ where
         id: String,
         uri: String,
     ) -> Result<Response<C>, ContractError> {
-        // Verify Sender is Owner
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // Verify Sender is a catalog manager or the owner
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
+        ensure_not_retired(deps.storage, CatalogKind::HoloKey, &id)?;
     
         let new_holokey = HoloKey {
             rarity,
@@ -633,13 +664,15 @@ where
         info: MessageInfo,
         glonfts: Vec<GloNFT>,
     ) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         for glonft in glonfts {
             // Ensure that each GloNFT has a probability of None
             if glonft.probability.is_some() {
                 return Err(ContractError::InvalidSpecialGloNFT {});
             }
+            validate_royalty(deps.api, &glonft.royalty)?;
+            ensure_not_retired(deps.storage, CatalogKind::SpecialGloNft, &glonft.id)?;
     
             SPECIAL_GLO_NFTS.save(deps.storage, &glonft.id, &glonft)?;
         }
@@ -655,7 +688,7 @@ where
         edit_type: SeasonEditType,
     ) -> Result<Response<C>, ContractError> {
         // Check ownership
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         // Load the season to edit
         let mut season = SEASONS.load(deps.storage, &season_id)
@@ -664,7 +697,7 @@ where
         // Handle the different edit types
         match edit_type {
             SeasonEditType::Rename(new_name) => {
-                season.id = new_name;
+                return self.rename_season(deps, season, new_name);
             },
             SeasonEditType::GloChip { rarity, glochip_id } => {
                 self.edit_season_glochip(deps.storage, rarity, glochip_id, &mut season)?;
@@ -682,48 +715,89 @@ where
             .add_attribute("season_id", season_id))
     }
 
+    /// Moves a season to a new storage key and repoints every GloChip and HoloKey assigned to it
+    fn rename_season(
+        &self,
+        deps: DepsMut,
+        mut season: Season,
+        new_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let old_id = season.id.clone();
+        if new_id.is_empty() {
+            return Err(ContractError::InvalidTokenId { token_id: new_id });
+        }
+        if SEASONS.has(deps.storage, &new_id) {
+            return Err(ContractError::SeasonAlreadyExists {});
+        }
+
+        let glochips = GLOCHIPS
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (id, mut glochip) in glochips {
+            if glochip.season_id.as_deref() == Some(old_id.as_str()) {
+                glochip.season_id = Some(new_id.clone());
+                GLOCHIPS.save(deps.storage, &id, &glochip)?;
+            }
+        }
+
+        let keys = HOLOKEYS
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (id, mut key) in keys {
+            if key.season_id.as_deref() == Some(old_id.as_str()) {
+                key.season_id = Some(new_id.clone());
+                HOLOKEYS.save(deps.storage, &id, &key)?;
+            }
+        }
+
+        season.id = new_id.clone();
+        SEASONS.remove(deps.storage, &old_id);
+        SEASONS.save(deps.storage, &new_id, &season)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "edit_season")
+            .add_attribute("season_id", new_id)
+            .add_attribute("previous_id", old_id))
+    }
+
     fn edit_season_glochip(
         &self,
         storage: &mut dyn Storage,
         rarity: Rarity,
-        glochip_id: Option<String>, // Updated to Option<String>
+        glochip_id: Option<String>, // None or empty clears the slot
         season: &mut Season,
     ) -> Result<(), ContractError> {
-        match glochip_id {
-            Some(id) if !id.is_empty() => {
-                let glochip = GLOCHIPS.load(storage, &id)
-                    .map_err(|_| ContractError::GloChipNotFound { glochip_id: id.clone() })?;
-                
-                if glochip.season_id.is_some() && glochip.season_id != Some(season.id.clone()) {
-                    // GloChip is already assigned to a different season
-                    return Err(ContractError::GloChipAlreadyAssigned {});
-                }
-    
-                if glochip.rarity != rarity {
-                    return Err(ContractError::MismatchedRarities {});
-                }
-    
-                // Assign the GloChip to the correct rarity slot in the season
-                match rarity {
-                    Rarity::Generic => season.generic_glochip = Some(id.clone()),
-                    Rarity::Esoteric => season.esoteric_glochip = Some(id.clone()),
-                    Rarity::Spectral => season.spectral_glochip = Some(id.clone()),
-                }
-    
-                // Update the GloChip's season ID and save it
-                let mut updated_glochip = glochip;
-                updated_glochip.season_id = Some(season.id.clone());
-                GLOCHIPS.save(storage, &id, &updated_glochip)?;
-            },
-            _ => {
-                // Remove the GloChip from the season
-                match rarity {
-                    Rarity::Generic => season.generic_glochip = None,
-                    Rarity::Esoteric => season.esoteric_glochip = None,
-                    Rarity::Spectral => season.spectral_glochip = None,
-                }
+        let season_id = season.id.clone();
+        let glochip_id = glochip_id.filter(|id| !id.is_empty());
+
+        if let Some(id) = &glochip_id {
+            let mut glochip = GLOCHIPS.load(storage, id)
+                .map_err(|_| ContractError::GloChipNotFound { glochip_id: id.clone() })?;
+
+            if glochip.season_id.is_some() && glochip.season_id.as_ref() != Some(&season_id) {
+                // GloChip is already assigned to a different season
+                return Err(ContractError::GloChipAlreadyAssigned {});
+            }
+
+            if glochip.rarity != rarity {
+                return Err(ContractError::MismatchedRarities {});
             }
+
+            glochip.season_id = Some(season_id.clone());
+            GLOCHIPS.save(storage, id, &glochip)?;
+        }
+
+        let slot = match rarity {
+            Rarity::Generic => &mut season.generic_glochip,
+            Rarity::Esoteric => &mut season.esoteric_glochip,
+            Rarity::Spectral => &mut season.spectral_glochip,
+        };
+        // The GloChip leaving the slot is free to join another season
+        if let Some(displaced) = slot.take().filter(|displaced| Some(displaced) != glochip_id.as_ref()) {
+            release_glochip(storage, &displaced, &season_id)?;
         }
+        *slot = glochip_id;
+
         Ok(())
     }
 
@@ -731,44 +805,40 @@ where
         &self,
         storage: &mut dyn Storage,
         rarity: Rarity,
-        key_id: Option<String>, // Updated to Option<String>
+        key_id: Option<String>, // None or empty clears the slot
         season: &mut Season,
     ) -> Result<(), ContractError> {
-        match key_id {
-            Some(id) if !id.is_empty() => {
-                let key = HOLOKEYS.load(storage, &id)
-                    .map_err(|_| ContractError::KeyNotFound { key_id: id.clone() })?;
-                
-                if key.season_id.is_some() && key.season_id != Some(season.id.clone()) {
-                    // HoloKey is already assigned to a different season
-                    return Err(ContractError::KeyAlreadyAssigned {});
-                }
-    
-                if key.rarity != rarity {
-                    return Err(ContractError::MismatchedRarities {});
-                }
-    
-                // Assign the key to the correct rarity slot in the season
-                match rarity {
-                    Rarity::Generic => season.generic_key = Some(id.clone()),
-                    Rarity::Esoteric => season.esoteric_key = Some(id.clone()),
-                    Rarity::Spectral => season.spectral_key = Some(id.clone()),
-                }
-    
-                // Update the HoloKey's season ID and save it
-                let mut updated_key = key;
-                updated_key.season_id = Some(season.id.clone());
-                HOLOKEYS.save(storage, &id, &updated_key)?;
-            },
-            _ => {
-                // Remove the HoloKey from the season
-                match rarity {
-                    Rarity::Generic => season.generic_key = None,
-                    Rarity::Esoteric => season.esoteric_key = None,
-                    Rarity::Spectral => season.spectral_key = None,
-                }
+        let season_id = season.id.clone();
+        let key_id = key_id.filter(|id| !id.is_empty());
+
+        if let Some(id) = &key_id {
+            let mut key = HOLOKEYS.load(storage, id)
+                .map_err(|_| ContractError::KeyNotFound { key_id: id.clone() })?;
+
+            if key.season_id.is_some() && key.season_id.as_ref() != Some(&season_id) {
+                // HoloKey is already assigned to a different season
+                return Err(ContractError::KeyAlreadyAssigned {});
+            }
+
+            if key.rarity != rarity {
+                return Err(ContractError::MismatchedRarities {});
             }
+
+            key.season_id = Some(season_id.clone());
+            HOLOKEYS.save(storage, id, &key)?;
+        }
+
+        let slot = match rarity {
+            Rarity::Generic => &mut season.generic_key,
+            Rarity::Esoteric => &mut season.esoteric_key,
+            Rarity::Spectral => &mut season.spectral_key,
+        };
+        // The HoloKey leaving the slot is free to join another season
+        if let Some(displaced) = slot.take().filter(|displaced| Some(displaced) != key_id.as_ref()) {
+            release_key(storage, &displaced, &season_id)?;
         }
+        *slot = key_id;
+
         Ok(())
     }
 
@@ -778,7 +848,7 @@ where
         info: MessageInfo,
         price_update_type: PriceUpdateType,
     ) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::PriceManager)?;
     
         match price_update_type {
             PriceUpdateType::SpecialGloChipPrice { id, new_price } => {
@@ -814,6 +884,54 @@ where
         Ok(Response::new().add_attribute("action", "update_prices"))
     }
 
+    pub fn update_royalties(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        royalty_update_type: RoyaltyUpdateType,
+    ) -> Result<Response<C>, ContractError> {
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
+
+        match royalty_update_type {
+            RoyaltyUpdateType::Collection { royalty } => {
+                validate_royalty(deps.api, &royalty)?;
+                match royalty {
+                    Some(royalty) => COLLECTION_ROYALTY.save(deps.storage, &royalty)?,
+                    None => COLLECTION_ROYALTY.remove(deps.storage),
+                }
+            },
+            RoyaltyUpdateType::GloChip { id, royalty } => {
+                validate_royalty(deps.api, &royalty)?;
+                let mut glochip = GLOCHIPS.load(deps.storage, &id)
+                    .map_err(|_| ContractError::GloChipNotFound { glochip_id: id.clone() })?;
+                glochip.royalty = royalty;
+                GLOCHIPS.save(deps.storage, &id, &glochip)?;
+            },
+            RoyaltyUpdateType::GloNFT { glochip_id, id, royalty } => {
+                validate_royalty(deps.api, &royalty)?;
+                match glochip_id {
+                    Some(glochip_id) => {
+                        // GloNFT opened from a GloChip, stored in the GloChip's items
+                        let mut glochip = GLOCHIPS.load(deps.storage, &glochip_id)
+                            .map_err(|_| ContractError::GloChipNotFound { glochip_id: glochip_id.clone() })?;
+                        let glonft = glochip.items.iter_mut().find(|g| g.id == id)
+                            .ok_or(ContractError::GloNFTNotFound {})?;
+                        glonft.royalty = royalty;
+                        GLOCHIPS.save(deps.storage, &glochip_id, &glochip)?;
+                    },
+                    None => {
+                        let mut glonft = SPECIAL_GLO_NFTS.load(deps.storage, &id)
+                            .map_err(|_| ContractError::GloNFTNotFound {})?;
+                        glonft.royalty = royalty;
+                        SPECIAL_GLO_NFTS.save(deps.storage, &id, &glonft)?;
+                    }
+                }
+            }
+        }
+
+        Ok(Response::new().add_attribute("action", "update_royalties"))
+    }
+
     fn get_price(
         &self,
         deps: Deps,
@@ -865,8 +983,8 @@ where
         glochip_id: String,
         rarity: Rarity,
     ) -> Result<Response<C>, ContractError> {
-        // Verify Sender is Owner
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // Verify Sender is a catalog manager or the owner
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         // Load the season to add the glochip to
         let mut season = SEASONS.load(deps.storage, &season_id)
@@ -928,11 +1046,20 @@ where
         key_id: String, // ID of the key to add
         key_type: KeyType,
     ) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         // Load the season to add the key ID to
         let mut season = SEASONS.load(deps.storage, &season_id)
             .map_err(|_| ContractError::SeasonNotFound {})?;
+
+        // Load the key so a later rename can find the season slot holding it
+        let mut key = HOLOKEYS.load(deps.storage, &key_id)
+            .map_err(|_| ContractError::KeyNotFound { key_id: key_id.clone() })?;
+
+        if key.season_id.is_some() {
+            // HoloKey is already assigned to a season
+            return Err(ContractError::KeyAlreadyAssigned {});
+        }
     
         // Check if the key ID is already set for the key type and update it
         match key_type {
@@ -958,6 +1085,10 @@ where
     
         // Save the updated season back to storage
         SEASONS.save(deps.storage, &season_id, &season)?;
+
+        // Set the season_id for the key and save it
+        key.season_id = Some(season_id.clone());
+        HOLOKEYS.save(deps.storage, &key_id, &key)?;
     
         // Return a response indicating the key was added to the season
         Ok(Response::new()
@@ -975,19 +1106,13 @@ where
         new_uri: Option<String>,
         new_items: Option<Vec<GloNFT>>,
     ) -> Result<Response<C>, ContractError> {
-        // Verify Owner
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // Verify Sender is a catalog manager or the owner
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         // Load existing GloChip
         let mut existing_glochip = GLOCHIPS.load(deps.storage, &glochip_id)
             .map_err(|_| ContractError::GloChipNotFound { glochip_id: glochip_id.clone() })?;
     
-        // Check and update new ID if provided
-        if let Some(id) = new_id {
-            // Optional: You might want to add checks to ensure the new ID is valid or not already in use
-            existing_glochip.id = id;
-        }
-    
         // Check and update new URI if provided
         if let Some(uri) = new_uri {
             existing_glochip.uri = uri;
@@ -1002,15 +1127,36 @@ where
             if total_probability != 100 {
                 return Err(ContractError::InvalidProbability {});
             }
+            for item in &items {
+                validate_royalty(deps.api, &item.royalty)?;
+            }
             existing_glochip.items = items;
         }
     
+        // Move the GloChip to its new ID, carrying the edition count and season slot along
+        if let Some(id) = new_id.filter(|id| id != &glochip_id) {
+            ensure_id_available(deps.storage, CatalogKind::GloChip, GLOCHIPS.has(deps.storage, &id), &glochip_id, &id)?;
+            if let Some(season_id) = &existing_glochip.season_id {
+                let mut season = SEASONS.load(deps.storage, season_id)?;
+                for slot in [&mut season.generic_glochip, &mut season.esoteric_glochip, &mut season.spectral_glochip] {
+                    if slot.as_deref() == Some(glochip_id.as_str()) {
+                        *slot = Some(id.clone());
+                    }
+                }
+                SEASONS.save(deps.storage, season_id, &season)?;
+            }
+            GLOCHIPS.remove(deps.storage, &glochip_id);
+            record_rename(deps.storage, CatalogKind::GloChip, &glochip_id, &id)?;
+            existing_glochip.id = id;
+        }
+
         // Save updated GloChip
         GLOCHIPS.save(deps.storage, &existing_glochip.id, &existing_glochip)?;
     
         Ok(Response::new()
             .add_attribute("action", "edit_glochip")
-            .add_attribute("glochip_id", existing_glochip.id))
+            .add_attribute("glochip_id", existing_glochip.id)
+            .add_attribute("previous_id", glochip_id))
     }
 
     pub fn edit_key(
@@ -1021,30 +1167,42 @@ where
         new_id: Option<String>,
         new_uri: Option<String>,
     ) -> Result<Response<C>, ContractError> {
-        // Verify Owner
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // Verify Sender is a catalog manager or the owner
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         // Load existing HoloKey
         let mut existing_key = HOLOKEYS.load(deps.storage, &key_id)
             .map_err(|_| ContractError::KeyNotFound { key_id: key_id.clone() })?;
     
-        // Check and update new ID if provided
-        if let Some(id) = new_id {
-            // Optional: You might want to add checks to ensure the new ID is valid or not already in use
-            existing_key.id = id;
-        }
-    
         // Check and update new URI if provided
         if let Some(uri) = new_uri {
             existing_key.uri = uri;
         }
+
+        // Move the HoloKey to its new ID and repoint the season slot holding it
+        if let Some(id) = new_id.filter(|id| id != &key_id) {
+            ensure_id_available(deps.storage, CatalogKind::HoloKey, HOLOKEYS.has(deps.storage, &id), &key_id, &id)?;
+            if let Some(season_id) = &existing_key.season_id {
+                let mut season = SEASONS.load(deps.storage, season_id)?;
+                for slot in [&mut season.generic_key, &mut season.esoteric_key, &mut season.spectral_key] {
+                    if slot.as_deref() == Some(key_id.as_str()) {
+                        *slot = Some(id.clone());
+                    }
+                }
+                SEASONS.save(deps.storage, season_id, &season)?;
+            }
+            HOLOKEYS.remove(deps.storage, &key_id);
+            record_rename(deps.storage, CatalogKind::HoloKey, &key_id, &id)?;
+            existing_key.id = id;
+        }
     
         // Save updated HoloKey
         HOLOKEYS.save(deps.storage, &existing_key.id, &existing_key)?;
     
         Ok(Response::new()
             .add_attribute("action", "edit_key")
-            .add_attribute("key_id", existing_key.id))
+            .add_attribute("key_id", existing_key.id)
+            .add_attribute("previous_id", key_id))
     }
 
     pub fn edit_special_glonft(
@@ -1055,22 +1213,28 @@ where
         new_id: Option<String>,
         new_uri: Option<String>,
     ) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         let mut glonft = SPECIAL_GLO_NFTS.load(deps.storage, &glonft_id)
             .map_err(|_| ContractError::GloNFTNotFound {})?;
     
-        if let Some(id) = new_id {
-            glonft.id = id;
-        }
-    
         if let Some(uri) = new_uri {
             glonft.uri = uri;
         }
+
+        if let Some(id) = new_id.filter(|id| id != &glonft_id) {
+            ensure_id_available(deps.storage, CatalogKind::SpecialGloNft, SPECIAL_GLO_NFTS.has(deps.storage, &id), &glonft_id, &id)?;
+            SPECIAL_GLO_NFTS.remove(deps.storage, &glonft_id);
+            record_rename(deps.storage, CatalogKind::SpecialGloNft, &glonft_id, &id)?;
+            glonft.id = id;
+        }
     
         SPECIAL_GLO_NFTS.save(deps.storage, &glonft.id, &glonft)?;
     
-        Ok(Response::new().add_attribute("action", "edit_special_glonft").add_attribute("glonft_id", glonft.id))
+        Ok(Response::new()
+            .add_attribute("action", "edit_special_glonft")
+            .add_attribute("glonft_id", glonft.id)
+            .add_attribute("previous_id", glonft_id))
     }
 
     pub fn delete_special_glonfts(
@@ -1079,15 +1243,127 @@ where
         info: MessageInfo,
         glonft_ids: Vec<String>,
     ) -> Result<Response<C>, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        assert_role(deps.storage, &info.sender, Role::CatalogManager)?;
     
         for glonft_id in glonft_ids {
             SPECIAL_GLO_NFTS.remove(deps.storage, &glonft_id);
+            retire_deleted_id(deps.storage, CatalogKind::SpecialGloNft, &glonft_id)?;
         }
     
         Ok(Response::new().add_attribute("action", "delete_special_glonfts"))
     }
 
+    pub fn update_pause(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        scopes: Vec<PauseScope>,
+        paused: bool,
+    ) -> Result<Response<C>, ContractError> {
+        // Guardians can pull the brake, only the owner can release it
+        if paused {
+            assert_role(deps.storage, &info.sender, Role::Guardian)?;
+        } else {
+            cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        }
+
+        if scopes.is_empty() {
+            return Err(ContractError::InvalidPauseScopes {});
+        }
+
+        let mut pause = PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default();
+        let mut response = Response::new()
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("sender", info.sender.clone());
+        for scope in scopes {
+            pause.set(scope, paused);
+            response = response.add_event(
+                Event::new("minter_pause")
+                    .add_attribute("scope", scope.as_str())
+                    .add_attribute("paused", paused.to_string())
+                    .add_attribute("sender", info.sender.clone()),
+            );
+        }
+        PAUSE_STATE.save(deps.storage, &pause)?;
+
+        Ok(response)
+    }
+
+    pub fn grant_role(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        address: String,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let address = deps.api.addr_validate(&address)?;
+        ROLES.save(deps.storage, (role.as_str(), &address), &Empty {})?;
+
+        Ok(Response::new()
+            .add_attribute("action", "grant_role")
+            .add_attribute("role", role.as_str())
+            .add_attribute("address", address))
+    }
+
+    pub fn revoke_role(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        address: String,
+    ) -> Result<Response<C>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let address = deps.api.addr_validate(&address)?;
+        if !ROLES.has(deps.storage, (role.as_str(), &address)) {
+            return Err(ContractError::RoleNotHeld { role: role.as_str().to_string() });
+        }
+        ROLES.remove(deps.storage, (role.as_str(), &address));
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_role")
+            .add_attribute("role", role.as_str())
+            .add_attribute("address", address))
+    }
+
+    /// Sends funds out of the contract. Defaults to the whole balance of the mint denom and the sender.
+    pub fn withdraw(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        denom: Option<String>,
+        amount: Option<Uint128>,
+        recipient: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        assert_role(deps.storage, &info.sender, Role::Treasurer)?;
+
+        let denom = denom.unwrap_or_else(|| MINT_DENOM.to_string());
+        let balance = deps.querier.query_balance(&env.contract.address, &denom)?.amount;
+        let amount = amount.unwrap_or(balance);
+        if amount.is_zero() || amount > balance {
+            return Err(ContractError::InsufficientFunds {});
+        }
+
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => info.sender.clone(),
+        };
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin { denom: denom.clone(), amount }],
+            })
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("denom", denom)
+            .add_attribute("amount", amount))
+    }
+
     pub fn update_ownership(
         deps: DepsMut,
         env: Env,
@@ -1116,6 +1392,7 @@ where
         recipient: String,
         token_id: String,
     ) -> Result<Response<C>, ContractError> {
+        assert_not_paused(deps.storage, PauseScope::Transfers)?;
         self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
 
         Ok(Response::new()
@@ -1134,6 +1411,8 @@ where
         token_id: String,
         msg: Binary,
     ) -> Result<Response<C>, ContractError> {
+        assert_not_paused(deps.storage, PauseScope::Transfers)?;
+
         // Transfer token
         self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
 
@@ -1249,6 +1528,116 @@ where
     }
 }
 
+/// Ensures a royalty, if set, has a valid payment address and a percentage of at most 100
+fn validate_royalty(api: &dyn Api, royalty: &Option<Royalty>) -> Result<(), ContractError> {
+    if let Some(royalty) = royalty {
+        api.addr_validate(royalty.payment_address.as_str())?;
+        if royalty.percentage > 100 {
+            return Err(ContractError::InvalidRoyaltyPercentage {});
+        }
+    }
+    Ok(())
+}
+
+/// Clears the season of a GloChip taken out of `season_id`'s slot
+fn release_glochip(storage: &mut dyn Storage, id: &str, season_id: &str) -> StdResult<()> {
+    if let Some(mut glochip) = GLOCHIPS.may_load(storage, id)? {
+        if glochip.season_id.as_deref() == Some(season_id) {
+            glochip.season_id = None;
+            GLOCHIPS.save(storage, id, &glochip)?;
+        }
+    }
+    Ok(())
+}
+
+/// Clears the season of a HoloKey taken out of `season_id`'s slot
+fn release_key(storage: &mut dyn Storage, id: &str, season_id: &str) -> StdResult<()> {
+    if let Some(mut key) = HOLOKEYS.may_load(storage, id)? {
+        if key.season_id.as_deref() == Some(season_id) {
+            key.season_id = None;
+            HOLOKEYS.save(storage, id, &key)?;
+        }
+    }
+    Ok(())
+}
+
+/// A rename target must not be a live entry or an id retired by another entry's rename.
+/// Taking back this entry's own former id is allowed, since its edition count carries on.
+fn ensure_id_available(
+    storage: &dyn Storage,
+    kind: CatalogKind,
+    in_use: bool,
+    current_id: &str,
+    new_id: &str,
+) -> Result<(), ContractError> {
+    if new_id.is_empty() {
+        return Err(ContractError::InvalidTokenId { token_id: new_id.to_string() });
+    }
+    let retired_by_other = RENAMED_IDS
+        .may_load(storage, (kind.as_str(), new_id))?
+        .is_some_and(|target| target != current_id);
+    if in_use || retired_by_other {
+        return Err(ContractError::IdAlreadyExists { id: new_id.to_string() });
+    }
+    Ok(())
+}
+
+/// New entries may not reuse a retired id, or their editions would clash with tokens minted under it
+fn ensure_not_retired(storage: &dyn Storage, kind: CatalogKind, id: &str) -> Result<(), ContractError> {
+    if RENAMED_IDS.has(storage, (kind.as_str(), id)) {
+        return Err(ContractError::IdAlreadyExists { id: id.to_string() });
+    }
+    Ok(())
+}
+
+/// Points `old_id`, and every id that already resolved to it, at `new_id`
+fn record_rename(storage: &mut dyn Storage, kind: CatalogKind, old_id: &str, new_id: &str) -> StdResult<()> {
+    let aliases = RENAMED_IDS
+        .prefix(kind.as_str())
+        .range(storage, None, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (alias, target) in aliases {
+        if target == old_id {
+            RENAMED_IDS.save(storage, (kind.as_str(), &alias), &new_id.to_string())?;
+        }
+    }
+
+    // The entry takes back one of its former ids
+    RENAMED_IDS.remove(storage, (kind.as_str(), new_id));
+    RENAMED_IDS.save(storage, (kind.as_str(), old_id), &new_id.to_string())
+}
+
+/// Keeps a deleted entry's former ids retired and retires its last id with them, so no new
+/// entry can take an id that editions minted before the delete still resolve to
+fn retire_deleted_id(storage: &mut dyn Storage, kind: CatalogKind, id: &str) -> StdResult<()> {
+    let renamed = RENAMED_IDS
+        .prefix(kind.as_str())
+        .range(storage, None, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .any(|(_, target)| target == id);
+    if renamed {
+        RENAMED_IDS.save(storage, (kind.as_str(), id), &id.to_string())?;
+    }
+    Ok(())
+}
+
+/// The owner passes every role check
+fn assert_role(storage: &dyn Storage, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if cw_ownable::is_owner(storage, sender)? || ROLES.has(storage, (role.as_str(), sender)) {
+        return Ok(());
+    }
+    Err(ContractError::RoleNotHeld { role: role.as_str().to_string() })
+}
+
+fn assert_not_paused(storage: &dyn Storage, scope: PauseScope) -> Result<(), ContractError> {
+    let pause = PAUSE_STATE.may_load(storage)?.unwrap_or_default();
+    if pause.is_paused(scope) {
+        return Err(ContractError::Paused { scope: scope.as_str().to_string() });
+    }
+    Ok(())
+}
+
 // helpers
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
@@ -12,7 +12,7 @@ mod contract_tests;
 mod multi_tests;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{Cw2981QueryMsg, ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
 pub use crate::state::Cw721Contract;
 
 // These types are re-exported so that contracts interacting with this
@@ -32,18 +32,12 @@ pub type Extension = Option<Empty>;
 pub const CONTRACT_NAME: &str = "Mothermint";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-// currently we only support migrating from 0.16.0. this is ok for now because
-// we have not released any 0.16.x where x != 0
-//
-// TODO: parse semvar so that any version 0.16.x can be migrated from
-pub const EXPECTED_FROM_VERSION: &str = "0.1.0";
-
 pub mod entry {
     use super::*;
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -71,31 +65,35 @@ pub mod entry {
     }
 
     #[cfg_attr(not(feature = "library"), entry_point)]
-    pub fn query(deps: Deps, env: Env, msg: QueryMsg<Empty>) -> StdResult<Binary> {
-        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
-        tract.query(deps, env, msg)
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg<Cw2981QueryMsg>) -> StdResult<Binary> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
+        match msg {
+            // cw2981 royalties are served through the extension query
+            QueryMsg::Extension { msg } => match msg {
+                Cw2981QueryMsg::RoyaltyInfo { token_id, sale_price } => {
+                    to_json_binary(&tract.royalty_info(deps, token_id, sale_price)?)
+                }
+                Cw2981QueryMsg::CheckRoyalties {} => to_json_binary(&tract.check_royalties()),
+            },
+            _ => tract.query(deps, env, msg),
+        }
     }
 
     #[cfg_attr(not(feature = "library"), entry_point)]
     pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-        // make sure the correct contract is being upgraded, and it's being
-        // upgraded from the correct version.
-        cw2::assert_contract_version(deps.as_ref().storage, CONTRACT_NAME, EXPECTED_FROM_VERSION)?;
-
-        // update contract version
-        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-
-        // perform the upgrade
-        upgrades::v0_1_1::migrate::<Extension, Empty, Empty, Empty>(deps)
+        // checks the stored contract name and version, then runs every upgrade step in between
+        upgrades::migrate(deps, CONTRACT_NAME, CONTRACT_VERSION)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_json, Uint128};
     use cw2::ContractVersion;
 
     use super::*;
+    use crate::msg::CheckRoyaltiesResponse;
 
     /// Make sure cw2 version info is properly initialized during instantiation.
     #[test]
@@ -123,4 +121,102 @@ mod tests {
             },
         );
     }
+
+    /// cw2981 queries are answered through the extension query rather than ignored.
+    #[test]
+    fn cw2981_queries_route_through_extension() {
+        let mut deps = mock_dependencies();
+        entry::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("larry", &[]),
+            InstantiateMsg {
+                name: "".into(),
+                symbol: "".into(),
+                minter: "larry".into(),
+            },
+        )
+        .unwrap();
+
+        let res: CheckRoyaltiesResponse = from_json(
+            entry::query(deps.as_ref(), mock_env(), QueryMsg::Extension { msg: Cw2981QueryMsg::CheckRoyalties {} }).unwrap(),
+        )
+        .unwrap();
+        assert!(res.royalty_payments);
+
+        // an unknown token is an error, where the plain extension query answers with an empty binary
+        let royalty_info = QueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltyInfo { token_id: "chip_1".into(), sale_price: Uint128::new(100) },
+        };
+        entry::query(deps.as_ref(), mock_env(), royalty_info).unwrap_err();
+    }
+
+    /// Migrating from the oldest supported version lands on the current version.
+    #[test]
+    fn migrate_from_supported_version() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, upgrades::MIN_MIGRATE_VERSION).unwrap();
+
+        let res = entry::migrate(deps.as_mut(), mock_env(), Empty {}).unwrap();
+        assert!(res.attributes.iter().any(|attr| attr.key == "from_version" && attr.value == upgrades::MIN_MIGRATE_VERSION));
+        assert!(res.attributes.iter().any(|attr| attr.key == "to_version" && attr.value == CONTRACT_VERSION));
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    /// A newer stored version must not be overwritten by older code.
+    #[test]
+    fn migrate_rejects_downgrade() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+
+        let err = entry::migrate(deps.as_mut(), mock_env(), Empty {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotDowngrade {
+                from: "999.0.0".into(),
+                to: CONTRACT_VERSION.into(),
+            },
+        );
+    }
+
+    /// Migrating over a different contract is refused.
+    #[test]
+    fn migrate_rejects_other_contract() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:marketplace", CONTRACT_VERSION).unwrap();
+
+        let err = entry::migrate(deps.as_mut(), mock_env(), Empty {}).unwrap_err();
+        assert!(matches!(err, ContractError::Version(cw2::VersionError::WrongContract { .. })));
+    }
+
+    /// Versions older than the first supported one have no upgrade path.
+    #[test]
+    fn migrate_rejects_unsupported_version() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        let err = entry::migrate(deps.as_mut(), mock_env(), Empty {}).unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedMigration { from: "0.0.1".into() });
+    }
+
+    /// Upgrade steps must stay in version order for chaining to work.
+    #[test]
+    fn migration_steps_are_ordered() {
+        let versions: Vec<_> = upgrades::MIGRATIONS
+            .iter()
+            .map(|step| upgrades::parse_version(step.version).unwrap())
+            .collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    /// Every shipped step must be reachable, so the crate version is bumped along with a new step.
+    #[test]
+    fn migration_steps_not_ahead_of_contract_version() {
+        let current = upgrades::parse_version(CONTRACT_VERSION).unwrap();
+        for step in upgrades::MIGRATIONS {
+            assert!(upgrades::parse_version(step.version).unwrap() <= current, "step {} is unreachable", step.version);
+        }
+    }
 }
@@ -1,10 +1,10 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Binary;
+use cosmwasm_std::{Addr, Binary, CustomMsg, Uint128};
 use cw721::Expiration;
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
 use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
-use crate::state::{TokenType, GloNFT, Rarity, KeyType, SeasonEditType, PriceUpdateType};
+use crate::state::{TokenType, GloNFT, Rarity, KeyType, SeasonEditType, PriceUpdateType, Royalty, RoyaltyUpdateType, PauseScope, PauseState, Role};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct OwnableTokensResponse {
@@ -89,6 +89,11 @@ pub enum ExecuteMsg<T, E> {
         price_update_type: PriceUpdateType,
     },
 
+    /// Updates collection-wide or per-item royalties, catalog manager only
+    UpdateRoyalties {
+        royalty_update_type: RoyaltyUpdateType,
+    },
+
     // Add a new glochip with its items and their probabilities
     CreateGloChip {
         id: String,
@@ -97,6 +102,7 @@ pub enum ExecuteMsg<T, E> {
         special: bool,
         price: Option<u128>,
         items: Vec<GloNFT>,
+        royalty: Option<Royalty>,
     },
 
     /// Create a HoloKey
@@ -151,6 +157,36 @@ pub enum ExecuteMsg<T, E> {
         key_id: String,
         key_type: KeyType,
     },
+
+    /// Halts the given scopes, callable by the owner or a guardian
+    Pause {
+        scopes: Vec<PauseScope>,
+    },
+
+    /// Resumes the given scopes, owner only
+    Unpause {
+        scopes: Vec<PauseScope>,
+    },
+
+    /// Gives an address an admin role, owner only
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+
+    /// Takes an admin role away from an address, owner only
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+
+    /// Sends funds held by the contract to `recipient` (default: sender), treasurer only.
+    /// `denom` defaults to the mint denom, and the full balance is withdrawn when `amount` is unset.
+    Withdraw {
+        denom: Option<String>,
+        amount: Option<Uint128>,
+        recipient: Option<String>,
+    },
 }
 
 #[cw_ownable_query]
@@ -255,6 +291,26 @@ pub enum QueryMsg<Q: JsonSchema> {
         owner: String,
         base_ids: Vec<String>,
     },
+
+    /// Returns the catalog traits (base id, rarity, season) of a minted token
+    #[returns(TokenTraitsResponse)]
+    TokenTraits { token_id: String },
+
+    /// Returns which scopes are paused
+    #[returns(PauseStateResponse)]
+    PauseState {},
+
+    /// Returns the roles held by an address
+    #[returns(RolesResponse)]
+    Roles { address: String },
+
+    /// Lists the addresses holding a role
+    #[returns(RoleMembersResponse)]
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
  
 /// Shows who can mint these tokens
@@ -263,6 +319,66 @@ pub struct MinterResponse {
     pub minter: Option<String>,
 }
 
+/// cw2981 royalty queries, sent through `QueryMsg::Extension`
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum Cw2981QueryMsg {
+    /// Should be called on sale to see if royalties are owed
+    /// by the marketplace selling the NFT, if CheckRoyalties
+    /// returns true
+    #[returns(RoyaltiesInfoResponse)]
+    RoyaltyInfo {
+        token_id: String,
+        // the denom of this sale must also be the denom returned by RoyaltiesInfoResponse
+        sale_price: Uint128,
+    },
+    /// Called against contract to determine if this NFT
+    /// implements royalties
+    #[returns(CheckRoyaltiesResponse)]
+    CheckRoyalties {},
+}
+
+impl CustomMsg for Cw2981QueryMsg {}
+
+#[cw_serde]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    // Note that this must be the same denom as that passed in to RoyaltyInfo
+    // rounding up or down is at the discretion of the implementer
+    pub royalty_amount: Uint128,
+}
+
+/// Shows if the contract implements royalties
+#[cw_serde]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenTraitsResponse {
+    /// GloChip, HoloKey or GloNFT id the token was minted from
+    pub base_id: String,
+    pub rarity: Option<Rarity>,
+    pub season_id: Option<String>,
+    pub special: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembersResponse {
+    pub role: Role,
+    pub members: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStateResponse {
+    pub paused: PauseState,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CheckOwnershipResponse {
     pub ownerships: Vec<bool>,
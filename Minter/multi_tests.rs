@@ -0,0 +1,116 @@
+#![cfg(test)]
+use cosmwasm_std::{coins, Addr, Empty, Uint128};
+use cw_multi_test::{App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor};
+
+use crate::entry;
+use crate::state::{GloChipDetails, GloNFT, Rarity, Role, TokenType, MINT_DENOM};
+use crate::{ContractError, ExecuteMsg, Extension, InstantiateMsg};
+
+const OWNER: &str = "owner";
+const TREASURER: &str = "treasurer";
+const BUYER: &str = "buyer";
+
+fn minter_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(entry::execute, entry::instantiate, entry::query)
+        .with_migrate(entry::migrate);
+    Box::new(contract)
+}
+
+fn mock_app() -> App {
+    AppBuilder::new().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &Addr::unchecked(BUYER), coins(1_000, MINT_DENOM))
+            .unwrap();
+    })
+}
+
+/// Instantiates the minter with a special GloChip selling for `price`
+fn setup(app: &mut App, price: u128) -> Addr {
+    let code_id = app.store_code(minter_contract());
+    let minter = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(OWNER),
+            &InstantiateMsg {
+                name: "GloChips".into(),
+                symbol: "GLO".into(),
+                minter: OWNER.into(),
+            },
+            &[],
+            "minter",
+            Some(OWNER.into()),
+        )
+        .unwrap();
+
+    let msg: ExecuteMsg<Extension, Empty> = ExecuteMsg::CreateGloChip {
+        id: "special".into(),
+        rarity: Rarity::Spectral,
+        uri: "ipfs://special".into(),
+        special: true,
+        price: Some(price),
+        items: vec![GloNFT {
+            id: "glonft".into(),
+            rarity: Rarity::Spectral,
+            uri: "ipfs://glonft".into(),
+            count: None,
+            price: None,
+            probability: Some(100),
+            royalty: None,
+        }],
+        royalty: None,
+    };
+    app.execute_contract(Addr::unchecked(OWNER), minter.clone(), &msg, &[]).unwrap();
+    minter
+}
+
+fn mint_special(app: &mut App, minter: &Addr, funds: u128) -> Result<AppResponse, ContractError> {
+    let msg: ExecuteMsg<Extension, Empty> = ExecuteMsg::Mint {
+        owner: BUYER.into(),
+        extension: None,
+        token_type: TokenType::GloChip(GloChipDetails {
+            special: true,
+            glochip_id: Some("special".into()),
+            performance_category: None,
+            season_id: None,
+        }),
+    };
+    app.execute_contract(Addr::unchecked(BUYER), minter.clone(), &msg, &coins(funds, MINT_DENOM))
+        .map_err(|err| err.downcast().unwrap())
+}
+
+#[test]
+fn paid_mints_are_withdrawn_by_the_treasurer() {
+    let mut app = mock_app();
+    let minter = setup(&mut app, 300);
+
+    let err = mint_special(&mut app, &minter, 299).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+
+    mint_special(&mut app, &minter, 300).unwrap();
+    mint_special(&mut app, &minter, 300).unwrap();
+    assert_eq!(app.wrap().query_balance(&minter, MINT_DENOM).unwrap().amount, Uint128::new(600));
+
+    let withdraw: ExecuteMsg<Extension, Empty> = ExecuteMsg::Withdraw {
+        denom: None,
+        amount: Some(Uint128::new(200)),
+        recipient: None,
+    };
+    let err = app
+        .execute_contract(Addr::unchecked(TREASURER), minter.clone(), &withdraw, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RoleNotHeld { role: "treasurer".into() },
+    );
+
+    let grant: ExecuteMsg<Extension, Empty> = ExecuteMsg::GrantRole {
+        role: Role::Treasurer,
+        address: TREASURER.into(),
+    };
+    app.execute_contract(Addr::unchecked(OWNER), minter.clone(), &grant, &[]).unwrap();
+    app.execute_contract(Addr::unchecked(TREASURER), minter.clone(), &withdraw, &[]).unwrap();
+
+    assert_eq!(app.wrap().query_balance(TREASURER, MINT_DENOM).unwrap().amount, Uint128::new(200));
+    assert_eq!(app.wrap().query_balance(&minter, MINT_DENOM).unwrap().amount, Uint128::new(400));
+}
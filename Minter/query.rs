@@ -5,6 +5,7 @@ use serde::{Serialize, Deserialize};
 
 use cosmwasm_std::{
     to_json_binary, Addr, Binary, BlockInfo, CustomMsg, Deps, Env, Order, StdError, StdResult,
+    Storage, Uint128,
 };
 
 use cw721::{
@@ -15,8 +16,14 @@ use cw721::{
 use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
 
-use crate::msg::{MinterResponse, QueryMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::msg::{
+    CheckRoyaltiesResponse, MinterResponse, PauseStateResponse, QueryMsg, RoleMembersResponse,
+    RolesResponse, RoyaltiesInfoResponse, TokenTraitsResponse,
+};
+use crate::state::{
+    Approval, Cw721Contract, Royalty, TokenInfo, COLLECTION_ROYALTY, GLOCHIPS, HOLOKEYS,
+    SPECIAL_GLO_NFTS, PAUSE_STATE, Role, ROLES, CatalogKind, RENAMED_IDS, resolve_id,
+};
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 100;
@@ -335,6 +342,14 @@ where
             QueryMsg::CheckOwnership { owner, base_ids } => {
                 to_json_binary(&self.check_ownership(deps, owner, base_ids)?)
             }
+            QueryMsg::PauseState {} => to_json_binary(&Self::pause_state(deps)?),
+            QueryMsg::Roles { address } => to_json_binary(&Self::roles(deps, address)?),
+            QueryMsg::RoleMembers { role, start_after, limit } => {
+                to_json_binary(&Self::role_members(deps, role, start_after, limit)?)
+            }
+            QueryMsg::TokenTraits { token_id } => {
+                to_json_binary(&self.token_traits(deps, token_id)?)
+            }
         }
     }
 
@@ -377,10 +392,132 @@ where
         Ok(TokensFullResponse { tokens: token_infos })
     }
 
+    /// cw2981 royalty owed on a sale of `token_id` at `sale_price`
+    pub fn royalty_info(
+        &self,
+        deps: Deps,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltiesInfoResponse> {
+        // Make sure the token exists before looking up its royalty
+        self.tokens.load(deps.storage, &token_id)?;
+
+        let response = match token_royalty(deps.storage, &token_id)? {
+            Some(royalty) => RoyaltiesInfoResponse {
+                address: royalty.payment_address.into_string(),
+                royalty_amount: sale_price.multiply_ratio(royalty.percentage, 100u128),
+            },
+            None => RoyaltiesInfoResponse {
+                address: String::new(),
+                royalty_amount: Uint128::zero(),
+            },
+        };
+
+        Ok(response)
+    }
+
+    /// Royalties are always reported through `royalty_info`, even when none are configured
+    pub fn check_royalties(&self) -> CheckRoyaltiesResponse {
+        CheckRoyaltiesResponse {
+            royalty_payments: true,
+        }
+    }
+
+    pub fn token_traits(&self, deps: Deps, token_id: String) -> StdResult<TokenTraitsResponse> {
+        // Make sure the token exists before looking up its definition
+        self.tokens.load(deps.storage, &token_id)?;
+
+        let base_id = base_token_id(&token_id).to_string();
+
+        // Report the current id of renamed entries
+        let glochip_id = resolve_id(deps.storage, CatalogKind::GloChip, &base_id)?;
+        if let Some(glochip) = GLOCHIPS.may_load(deps.storage, &glochip_id)? {
+            return Ok(TokenTraitsResponse {
+                base_id: glochip_id,
+                rarity: Some(glochip.rarity),
+                season_id: glochip.season_id,
+                special: glochip.special,
+            });
+        }
+        let key_id = resolve_id(deps.storage, CatalogKind::HoloKey, &base_id)?;
+        if let Some(key) = HOLOKEYS.may_load(deps.storage, &key_id)? {
+            return Ok(TokenTraitsResponse {
+                base_id: key_id,
+                rarity: Some(key.rarity),
+                season_id: key.season_id,
+                special: false,
+            });
+        }
+        let glonft_id = resolve_id(deps.storage, CatalogKind::SpecialGloNft, &base_id)?;
+        if let Some(glonft) = SPECIAL_GLO_NFTS.may_load(deps.storage, &glonft_id)? {
+            return Ok(TokenTraitsResponse {
+                base_id: glonft_id,
+                rarity: Some(glonft.rarity),
+                season_id: None,
+                special: true,
+            });
+        }
+
+        // GloNFTs minted through an opening inherit the season of their GloChip
+        for item in GLOCHIPS.range(deps.storage, None, None, Order::Ascending) {
+            let (_, glochip) = item?;
+            if let Some(glonft) = glochip.items.iter().find(|g| g.id == base_id) {
+                return Ok(TokenTraitsResponse {
+                    rarity: Some(glonft.rarity.clone()),
+                    base_id,
+                    season_id: glochip.season_id,
+                    special: glochip.special,
+                });
+            }
+        }
+
+        Ok(TokenTraitsResponse {
+            base_id,
+            rarity: None,
+            season_id: None,
+            special: false,
+        })
+    }
+
     pub fn ownership(deps: Deps) -> StdResult<cw_ownable::Ownership<Addr>> {
         cw_ownable::get_ownership(deps.storage)
     }
 
+    pub fn roles(deps: Deps, address: String) -> StdResult<RolesResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let roles = Role::all()
+            .into_iter()
+            .filter(|role| ROLES.has(deps.storage, (role.as_str(), &address)))
+            .collect();
+
+        Ok(RolesResponse { roles })
+    }
+
+    pub fn role_members(
+        deps: Deps,
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RoleMembersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let members = ROLES
+            .prefix(role.as_str())
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(RoleMembersResponse { role, members })
+    }
+
+    pub fn pause_state(deps: Deps) -> StdResult<PauseStateResponse> {
+        Ok(PauseStateResponse {
+            paused: PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default(),
+        })
+    }
+
     fn check_ownership(
         &self,
         deps: Deps,
@@ -389,13 +526,21 @@ where
     ) -> StdResult<Vec<bool>> {
         let owner_addr = deps.api.addr_validate(&owner)?;
 
+        // Editions minted before a rename carry one of the entry's former ids
+        let renamed = RENAMED_IDS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
         let results = base_ids.into_iter().map(|base_id| {
+            let mut prefixes = vec![base_id.clone()];
+            prefixes.extend(renamed.iter().filter(|(_, target)| target == &base_id).map(|((_, old_id), _)| old_id.clone()));
+
             let tokens = self.tokens.idx.owner.prefix(owner_addr.clone());
             let mut range = tokens.range(deps.storage, None, None, Order::Ascending);
 
             range.any(|item| {
                 match item {
-                    Ok((token_id, _)) => token_id.starts_with(&base_id),
+                    Ok((token_id, _)) => prefixes.iter().any(|prefix| token_id.starts_with(prefix)),
                     Err(_) => false,
                 }
             })
@@ -405,6 +550,41 @@ where
     }
 }
 
+/// Token IDs are "<base id>_<count>"
+fn base_token_id(token_id: &str) -> &str {
+    token_id.rsplitn(2, '_').last().unwrap_or(token_id)
+}
+
+/// Resolves the royalty of a token: GloNFT override, then GloChip override, then collection default
+fn token_royalty(storage: &dyn Storage, token_id: &str) -> StdResult<Option<Royalty>> {
+    let base_id = base_token_id(token_id);
+    let glochip_id = resolve_id(storage, CatalogKind::GloChip, base_id)?;
+    let glonft_id = resolve_id(storage, CatalogKind::SpecialGloNft, base_id)?;
+
+    if let Some(glochip) = GLOCHIPS.may_load(storage, &glochip_id)? {
+        if glochip.royalty.is_some() {
+            return Ok(glochip.royalty);
+        }
+    } else if let Some(glonft) = SPECIAL_GLO_NFTS.may_load(storage, &glonft_id)? {
+        if glonft.royalty.is_some() {
+            return Ok(glonft.royalty);
+        }
+    } else {
+        // GloNFTs minted through an opening are defined in their GloChip's items
+        for item in GLOCHIPS.range(storage, None, None, Order::Ascending) {
+            let (_, glochip) = item?;
+            if let Some(glonft) = glochip.items.iter().find(|g| g.id == base_id) {
+                if let Some(royalty) = glonft.royalty.clone().or(glochip.royalty) {
+                    return Ok(Some(royalty));
+                }
+                break;
+            }
+        }
+    }
+
+    COLLECTION_ROYALTY.may_load(storage)
+}
+
 fn parse_approval(item: StdResult<(Addr, Expiration)>) -> StdResult<cw721::Approval> {
     item.map(|(spender, expires)| cw721::Approval {
         spender: spender.to_string(),
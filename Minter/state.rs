@@ -3,7 +3,7 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, CustomMsg, StdResult, Storage};
+use cosmwasm_std::{Addr, BlockInfo, CustomMsg, Empty, StdResult, Storage};
 
 use cw721::{ContractInfoResponse, Cw721, Expiration};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
@@ -289,6 +289,21 @@ pub enum PriceUpdateType {
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum RoyaltyUpdateType {
+    Collection { royalty: Option<Royalty> },
+    GloChip { id: String, royalty: Option<Royalty> },
+    // glochip_id is None for special GloNFTs, Some for GloNFTs opened from a GloChip
+    GloNFT { glochip_id: Option<String>, id: String, royalty: Option<Royalty> },
+}
+
+/// cw2981 royalty terms, either collection-wide or overriding a single GloChip/GloNFT
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Royalty {
+    pub payment_address: Addr,
+    pub percentage: u64, // Royalty in percentage (0-100) of the sale price
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct GloChip {
     pub rarity: Rarity,
@@ -299,6 +314,7 @@ pub struct GloChip {
     pub count: u64,
     pub price: Option<u128>,
     pub season_id: Option<String>,
+    pub royalty: Option<Royalty>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -318,9 +334,116 @@ pub struct GloNFT {
     pub count: Option<u64>,
     pub price: Option<u128>,
     pub probability: Option<u8>, // Probability in percentage (0-100) for GloNFTs in a GloChip
+    pub royalty: Option<Royalty>,
+}
+
+/// Catalog entries whose minted tokens are named "<id>_<count>"
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub enum CatalogKind {
+    GloChip,
+    HoloKey,
+    SpecialGloNft,
+}
+
+impl CatalogKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogKind::GloChip => "glochip",
+            CatalogKind::HoloKey => "holokey",
+            CatalogKind::SpecialGloNft => "special_glonft",
+        }
+    }
+}
+
+/// Ids retired by a rename, keyed by (kind, old id) and pointing at the id the entry lives
+/// under now. Editions minted before the rename keep their old prefix and are resolved here.
+pub const RENAMED_IDS: Map<(&str, &str), String> = Map::new("renamed_ids");
+
+/// Current catalog id for a base id taken from a token id
+pub fn resolve_id(storage: &dyn Storage, kind: CatalogKind, id: &str) -> StdResult<String> {
+    Ok(RENAMED_IDS
+        .may_load(storage, (kind.as_str(), id))?
+        .unwrap_or_else(|| id.to_string()))
 }
 
 pub const GLOCHIPS: Map<&str, GloChip> = Map::new("glochips");
 pub const HOLOKEYS: Map<&str, HoloKey> = Map::new("holokeys");
 pub const SPECIAL_GLO_NFTS: Map<&str, GloNFT> = Map::new("special_glo_nfts");
-pub const HOLOKEY_PRICING: Item<HoloKeyPricing> = Item::new("holokey_pricing");
\ No newline at end of file
+pub const HOLOKEY_PRICING: Item<HoloKeyPricing> = Item::new("holokey_pricing");
+pub const COLLECTION_ROYALTY: Item<Royalty> = Item::new("collection_royalty");
+
+/// Denom mint prices are paid in
+pub const MINT_DENOM: &str = "uluna";
+
+/// Parts of the contract that can be halted during an incident
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub enum PauseScope {
+    PaidMints,
+    Openings,
+    Transfers,
+}
+
+impl PauseScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseScope::PaidMints => "paid_mints",
+            PauseScope::Openings => "openings",
+            PauseScope::Transfers => "transfers",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseState {
+    pub paid_mints: bool,
+    pub openings: bool,
+    pub transfers: bool,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, scope: PauseScope) -> bool {
+        match scope {
+            PauseScope::PaidMints => self.paid_mints,
+            PauseScope::Openings => self.openings,
+            PauseScope::Transfers => self.transfers,
+        }
+    }
+
+    pub fn set(&mut self, scope: PauseScope, paused: bool) {
+        match scope {
+            PauseScope::PaidMints => self.paid_mints = paused,
+            PauseScope::Openings => self.openings = paused,
+            PauseScope::Transfers => self.transfers = paused,
+        }
+    }
+}
+
+pub const PAUSE_STATE: Item<PauseState> = Item::new("pause_state");
+
+/// Admin roles the owner can assign, each to any number of addresses
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub enum Role {
+    CatalogManager, // Create and edit seasons, GloChips, HoloKeys, special GloNFTs and royalties
+    PriceManager, // Update prices
+    RewardDistributor, // Mint reward GloChips
+    Treasurer, // Withdraw funds held by the contract
+    Guardian, // Pause, but never unpause
+}
+
+impl Role {
+    pub fn all() -> Vec<Role> {
+        vec![Role::CatalogManager, Role::PriceManager, Role::RewardDistributor, Role::Treasurer, Role::Guardian]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::CatalogManager => "catalog_manager",
+            Role::PriceManager => "price_manager",
+            Role::RewardDistributor => "reward_distributor",
+            Role::Treasurer => "treasurer",
+            Role::Guardian => "guardian",
+        }
+    }
+}
+
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
\ No newline at end of file
@@ -1 +1,77 @@
-pub mod v0_1_1;
\ No newline at end of file
+use cosmwasm_std::{DepsMut, Response};
+use semver::Version;
+
+use crate::ContractError;
+
+/// Oldest deployed version that can still be migrated to the current one
+pub const MIN_MIGRATE_VERSION: &str = "0.1.0";
+
+/// A storage transformation that brings state up to the layout of `version`
+pub struct MigrationStep {
+    pub version: &'static str,
+    pub migrate: fn(DepsMut) -> Result<(), ContractError>,
+}
+
+/// Every step ever shipped, oldest first. A migration runs each step newer than the
+/// stored version and no newer than the code being deployed.
+///
+/// No release since 0.1.0 has changed the stored layout, so there is nothing to run yet
+/// and a migration only records the new version.
+pub const MIGRATIONS: &[MigrationStep] = &[];
+
+pub fn parse_version(version: &str) -> Result<Version, ContractError> {
+    version
+        .parse()
+        .map_err(|_| ContractError::InvalidVersion { version: version.to_string() })
+}
+
+pub fn migrate(
+    mut deps: DepsMut,
+    contract_name: &str,
+    contract_version: &str,
+) -> Result<Response, ContractError> {
+    let stored = cw2::get_contract_version(deps.storage)?;
+    if stored.contract != contract_name {
+        return Err(cw2::VersionError::WrongContract {
+            expected: contract_name.to_string(),
+            found: stored.contract,
+        }
+        .into());
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(contract_version)?;
+    if to < from {
+        return Err(ContractError::CannotDowngrade {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+    if from < parse_version(MIN_MIGRATE_VERSION)? {
+        return Err(ContractError::UnsupportedMigration { from: from.to_string() });
+    }
+
+    let mut applied = vec![];
+    for step in MIGRATIONS {
+        let step_version = parse_version(step.version)?;
+        // A step shipped in this code but above its version would never run, so the crate version was not bumped
+        if step_version > to {
+            return Err(ContractError::MigrationAheadOfVersion {
+                step: step.version.to_string(),
+                version: to.to_string(),
+            });
+        }
+        if step_version > from {
+            (step.migrate)(deps.branch())?;
+            applied.push(step.version);
+        }
+    }
+
+    cw2::set_contract_version(deps.storage, contract_name, contract_version)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string())
+        .add_attribute("steps", applied.join(",")))
+}
@@ -1,19 +0,0 @@
-use cosmwasm_std::{CustomMsg, DepsMut, Response};
-use serde::{de::DeserializeOwned, Serialize};
-
-use crate::ContractError;
-
-pub fn migrate<T, C, E, Q>(_deps: DepsMut) -> Result<Response<C>, ContractError>
-where
-    T: Serialize + DeserializeOwned + Clone,
-    Q: CustomMsg,
-    E: CustomMsg,
-{
-    // You can add migration logic here if needed in future versions.
-
-    // For now, just return a response indicating a successful migration.
-    Ok(Response::new()
-        .add_attribute("action", "migrate")
-        .add_attribute("from_version", "current_version") // Replace with your current version
-        .add_attribute("to_version", "new_version")) // Replace with your new version
-}
\ No newline at end of file